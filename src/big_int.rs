@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Mul, Neg};
+
+// the base of each BigInt limb, chosen so a limb is 9 decimal digits
+const BIG_INT_BASE: u64 = 1_000_000_000;
+
+// an arbitrary precision integer stored as base 10^9 limbs, least significant first. Zero has no
+// limbs and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_isize(value: isize) -> BigInt {
+        let mut magnitude = value.unsigned_abs() as u64;
+        let mut limbs = Vec::new();
+
+        while magnitude > 0 {
+            limbs.push((magnitude % BIG_INT_BASE) as u32);
+            magnitude /= BIG_INT_BASE;
+        }
+
+        BigInt::new(value < 0, limbs)
+    }
+
+    // parse a run of ascii digits, 9 at a time starting from the least significant end
+    pub fn parse(digits: &[u8]) -> BigInt {
+        let limbs = digits
+            .rchunks(9)
+            .map(|chunk| {
+                chunk.iter().fold(0u32, |limb, digit| limb * 10 + (digit - b'0') as u32)
+            })
+            .collect();
+
+        BigInt::new(false, limbs)
+    }
+
+    pub fn to_isize(&self) -> Option<isize> {
+        let magnitude = self.limbs.iter().rev().try_fold(0i128, |value, limb| {
+            value.checked_mul(BIG_INT_BASE as i128)?.checked_add(*limb as i128)
+        })?;
+
+        let value = match self.negative {
+            true => -magnitude,
+            false => magnitude,
+        };
+        isize::try_from(value).ok()
+    }
+}
+
+fn compare_magnitudes(lhs: &[u32], rhs: &[u32]) -> Ordering {
+    lhs.len()
+        .cmp(&rhs.len())
+        .then_with(|| lhs.iter().rev().cmp(rhs.iter().rev()))
+}
+
+fn add_magnitudes(lhs: &[u32], rhs: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(lhs.len().max(rhs.len()) + 1);
+    let mut carry = 0u64;
+
+    for i in 0..lhs.len().max(rhs.len()) {
+        let total = *lhs.get(i).unwrap_or(&0) as u64 + *rhs.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push((total % BIG_INT_BASE) as u32);
+        carry = total / BIG_INT_BASE;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+
+    limbs
+}
+
+// subtract the smaller magnitude rhs from lhs
+fn subtract_magnitudes(lhs: &[u32], rhs: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(lhs.len());
+    let mut borrow = 0i64;
+
+    for (i, limb) in lhs.iter().enumerate() {
+        let mut difference = *limb as i64 - *rhs.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += BIG_INT_BASE as i64;
+            borrow = 1;
+        }
+        limbs.push(difference as u32);
+    }
+
+    limbs
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> Self::Output {
+        if self.negative == rhs.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.limbs, &rhs.limbs))
+        }
+
+        // the signs differ, so the result takes the sign of whichever has the larger magnitude
+        match compare_magnitudes(&self.limbs, &rhs.limbs) {
+            Ordering::Less => BigInt::new(rhs.negative, subtract_magnitudes(&rhs.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, subtract_magnitudes(&self.limbs, &rhs.limbs)),
+        }
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> Self::Output {
+        let mut limbs = vec![0u64; self.limbs.len() + rhs.limbs.len()];
+
+        for (i, lhs_limb) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, rhs_limb) in rhs.limbs.iter().enumerate() {
+                let total = limbs[i + j] + *lhs_limb as u64 * *rhs_limb as u64 + carry;
+                limbs[i + j] = total % BIG_INT_BASE;
+                carry = total / BIG_INT_BASE;
+            }
+            limbs[i + rhs.limbs.len()] += carry;
+        }
+
+        BigInt::new(
+            self.negative != rhs.negative,
+            limbs.into_iter().map(|limb| limb as u32).collect(),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some((most_significant, rest)) = self.limbs.split_last() else {
+            return write!(f, "0")
+        };
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{most_significant}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_test() {
+        let big = BigInt::parse(b"123456789012345678901234567890");
+        assert_eq!(big.to_string(), "123456789012345678901234567890");
+        assert_eq!(big.to_isize(), None);
+
+        assert_eq!((&big * &BigInt::from_isize(-10)).to_string(), "-1234567890123456789012345678900");
+        assert_eq!((&big + &-&big), BigInt::from_isize(0));
+        assert_eq!((&BigInt::from_isize(isize::MAX) + &BigInt::from_isize(1)).to_string(), "9223372036854775808");
+        assert_eq!((&BigInt::from_isize(5) + &BigInt::from_isize(-1_000_000_007)).to_isize(), Some(-1_000_000_002));
+    }
+
+    #[test]
+    fn zero_test() {
+        let zero = BigInt::parse(b"000");
+
+        assert_eq!(zero, BigInt::from_isize(0));
+        assert_eq!(zero.to_string(), "0");
+        assert_eq!(-&zero, zero);
+        assert_eq!(&BigInt::from_isize(-3) * &zero, zero);
+    }
+}
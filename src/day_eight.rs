@@ -2,6 +2,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::ops::{Add, Sub};
 use itertools::Itertools;
+use crate::grid;
+use crate::grid::GridError;
 
 pub fn part_one() -> isize {
     let antenna_map = build_antenna_map("inputs/day_eight_input.txt");
@@ -78,6 +80,7 @@ fn build_antenna_map(file: &str) -> AntennaMap {
         .expect("Should have been able to read the file");
 
     AntennaMap::new(&contents)
+        .expect("Should have been able to parse the grid")
 }
 
 #[derive(Debug, Eq, Hash, Copy, Clone)]
@@ -135,10 +138,11 @@ struct AntennaMap {
 }
 
 impl AntennaMap {
-    fn new(map_string: &str) -> AntennaMap {
-        let height = map_string.chars().filter(|c| *c == '\n').count() as isize + 1;
-        let width = map_string.find('\n').unwrap() as isize;
-        let map_rows = map_string.split('\n');
+    fn new(map_string: &str) -> Result<AntennaMap, GridError> {
+        let grid = grid::parse_rows(map_string)?;
+        let height = grid.height;
+        let width = grid.width;
+        let map_rows = grid.rows;
         let mut antenna_groups: HashMap<char, Vec<Position>> = HashMap::new();
 
         let mut y = 0;
@@ -155,11 +159,11 @@ impl AntennaMap {
             y += 1;
         }
 
-        AntennaMap {
+        Ok(AntennaMap {
             antenna_groups,
             height,
             width,
-        }
+        })
     }
 
     fn is_in_bounds(&self, position: &Position) -> bool {
@@ -1,60 +1,237 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
 use std::fs;
+use std::time::Instant;
+use itertools::Itertools;
 
 type RuleMap = HashMap<isize, Vec<isize>>;
 
+// pages are two digit numbers, so every page fits in a u128 bitset
+const MAX_PAGES: usize = 100;
+
 pub fn part_one() -> isize {
-    let rule_comparer = RuleComparer::new("inputs/day_five_rule_input.txt");
+    middle_page_sum("inputs/day_five_input.txt", true)
+        .unwrap_or_else(|error| panic!("Should have been able to order every update: {error}"))
+}
+
+pub fn part_two() -> isize {
+    middle_page_sum("inputs/day_five_input.txt", false)
+        .unwrap_or_else(|error| panic!("Should have been able to order every update: {error}"))
+}
 
-    let lists = process_lists("inputs/day_five_list_input.txt");
+// sum the middle pages of the updates that are already correctly ordered, or of the incorrectly
+// ordered ones once they've been sorted
+fn middle_page_sum(file: &str, correctly_ordered: bool) -> Result<isize, UpdateError> {
+    let input = process_input(file);
+    let rule_matrix = RuleMatrix::new(&input.rules);
 
-    lists.iter()
-        // turn the list into a tuple of the original list and a new sorted list. This sorted list
-        // will be sorted using the RuleComparer
-        .map(|list| {
-            let mut sorted_list = list.clone();
-            sorted_list.sort_by(|x, y| rule_comparer.compare(*x, *y));
+    let lists = input.updates;
 
-            (list, sorted_list)
-        })
-        // filter only the lists that match. these are already correctly sorted lists.
-        .filter(|(list, sorted_list)| list_matches(list, &sorted_list))
-        // get the middle number in the list and sum them up
-        .map(|(_, sorted_list)| {
-            let middle_index = sorted_list.len() / 2;
-            *sorted_list.get(middle_index).unwrap()
-        })
-        .sum()
+    let mut sum = 0;
+    for list in &lists {
+        // the sorted list is the topological order of the rules that apply to the pages in the list
+        let sorted_list = sort_update(&rule_matrix, list)
+            .map_err(|error| UpdateError { update: list.clone(), error })?;
+
+        if list_matches(list, &sorted_list) == correctly_ordered {
+            sum += sorted_list[sorted_list.len() / 2];
+        }
+    }
+
+    Ok(sum)
 }
 
-pub fn part_two() -> isize {
-    let rule_comparer = RuleComparer::new("inputs/day_five_rule_input.txt");
+// an update whose pages the rules can't put in a single order
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UpdateError {
+    pub update: Vec<isize>,
+    pub error: OrderError,
+}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "update {} can't be ordered: {}", self.update.iter().join(","), self.error)
+    }
+}
 
-    let lists = process_lists("inputs/day_five_list_input.txt");
+// print why every incorrectly ordered update is wrong and how to fix it
+pub fn run(options: &[String]) {
+    if options.iter().any(|option| option == "--explain") {
+        let input = process_input("inputs/day_five_input.txt");
+        let rule_matrix = RuleMatrix::new(&input.rules);
 
-    lists.iter()
-        // turn the list into a tuple of the original list and a new sorted list. This sorted list
-        // will be sorted using the RuleComparer
-        .map(|list| {
-            let mut sorted_list = list.clone();
-            sorted_list.sort_by(|x, y| rule_comparer.compare(*x, *y));
+        for list in &input.updates {
+            let explanation = match explain_update(&rule_matrix, list) {
+                Ok(explanation) => explanation,
+                Err(error) => {
+                    println!("{}", list.iter().join(","));
+                    println!("  can't be ordered: {error}");
+                    continue
+                }
+            };
+            if explanation.violations.is_empty() {
+                continue
+            }
 
-            (list, sorted_list)
-        })
-        // filter only the lists that match. these are already correctly sorted lists.
-        .filter(|(list, sorted_list)| !list_matches(list, &sorted_list))
-        // get the middle number in the list and sum them up
-        .map(|(_, sorted_list)| {
-            let middle_index = sorted_list.len() / 2;
-            *sorted_list.get(middle_index).unwrap()
-        })
-        .sum()
+            println!("{}", list.iter().join(","));
+            for (before, after) in &explanation.violations {
+                println!("  {before} is printed after {after} despite the rule {before}|{after}");
+            }
+            for Move { page, to } in &explanation.moves {
+                println!("  move {page} to position {to}");
+            }
+        }
+    }
+
+    if options.iter().any(|option| option == "--analyze") {
+        let input = process_input("inputs/day_five_input.txt");
+        print_analysis(&input.rules);
+    }
+
+    if let Some(file) = crate::option_value(options, "--dot") {
+        let rules = process_input("inputs/day_five_input.txt").rules;
+        fs::write(file, rules_to_dot(&rules, &redundant_rules(&rules)))
+            .expect("Should have been able to write the file");
+    }
+
+    if options.iter().any(|option| option == "--bench") {
+        benchmark();
+    }
+
+    // both parts sort every update, so report an update that can't be sorted before they panic on it
+    if let Err(error) = middle_page_sum("inputs/day_five_input.txt", true) {
+        eprintln!("{error}");
+        return
+    }
+
+    println!("Part one: {}", part_one());
+    println!("Part two: {}", part_two());
 }
 
+// time ordering every update with the RuleMap lookups against the RuleMatrix lookups
+pub fn benchmark() {
+    let PuzzleInput { rules, updates: lists } = process_input("inputs/day_five_input.txt");
+    let runs = 100;
+
+    let now = Instant::now();
+    let rule_matrix = RuleMatrix::new(&rules);
+    println!("building the rule matrix: {:.2?}", now.elapsed());
+
+    fn time(name: &str, runs: u32, lists: &[Vec<isize>], ordering_rules: &impl OrderingRules) {
+        let now = Instant::now();
+        for _ in 0..runs {
+            for list in lists {
+                let _ = sort_update(ordering_rules, list);
+                let _ = explain_update(ordering_rules, list);
+            }
+        }
+        println!("{name}: {:.2?} per run", now.elapsed() / runs);
+    }
 
+    time("rule map", runs, &lists, &RuleComparer::new(rules));
+    time("rule matrix", runs, &lists, &rule_matrix);
+}
+
+fn print_analysis(rules: &RuleMap) {
+    let rule_count = rules.values().map(|afters| afters.len()).sum::<usize>();
+    println!("{} pages, {rule_count} rules", rule_pages(rules).len());
+
+    let components = strongly_connected_components(rules);
+    println!("{} strongly connected components", components.len());
+    for component in components.iter().filter(|component| component.len() > 1) {
+        println!("  {} pages: {}", component.len(), component.iter().join(", "));
+    }
+
+    let redundant = redundant_rules(rules);
+    println!("{} rules are implied by the other rules", redundant.len());
+    for (before, after) in redundant {
+        println!("  {before}|{after}");
+    }
+}
 
-fn list_matches(list_one: &Vec<isize>, list_two: &Vec<isize>) -> bool {
+fn sort_update(ordering_rules: &impl OrderingRules, list: &[isize]) -> Result<Vec<isize>, OrderError> {
+    DependencyGraph::new(ordering_rules, list).sort()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Explanation {
+    // every (before, after) rule the update breaks by printing after first
+    pub violations: Vec<(isize, isize)>,
+    // the fewest moves that put the update in order, applied one after the other
+    pub moves: Vec<Move>,
+}
+
+// take a page out of the update and put it back in at an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    pub page: isize,
+    pub to: usize,
+}
+
+fn explain_update(ordering_rules: &impl OrderingRules, list: &[isize]) -> Result<Explanation, OrderError> {
+    let violations = list
+        .iter()
+        .tuple_combinations()
+        .filter(|(first, second)| ordering_rules.compare(**first, **second) == Ordering::Greater)
+        .map(|(first, second)| (*second, *first))
+        .collect();
+
+    Ok(Explanation {
+        violations,
+        moves: minimal_moves(list, &sort_update(ordering_rules, list)?),
+    })
+}
+
+// The pages that don't have to move are the longest run of pages, not necessarily next to each
+// other, that are already in sorted order. Every other page has to move once, which is the fewest
+// moves possible, and each is moved to just after the page that comes before it in sorted order.
+fn minimal_moves(list: &[isize], sorted_list: &[isize]) -> Vec<Move> {
+    let rank = |page: &isize| sorted_list.iter().position(|p| p == page).unwrap();
+    let ranks = list.iter().map(rank).collect::<Vec<usize>>();
+
+    // longest increasing subsequence of ranks, keeping the previous index to walk it back
+    let mut lengths = vec![1; ranks.len()];
+    let mut previous = vec![None; ranks.len()];
+    for i in 0..ranks.len() {
+        for j in 0..i {
+            if ranks[j] < ranks[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+
+    let mut settled = vec![false; sorted_list.len()];
+    let mut index = (0..ranks.len()).max_by_key(|i| (lengths[*i], std::cmp::Reverse(*i)));
+    while let Some(i) = index {
+        settled[ranks[i]] = true;
+        index = previous[i];
+    }
+
+    let mut current = list.to_vec();
+    let mut moves = Vec::new();
+
+    for (rank, page) in sorted_list.iter().enumerate() {
+        if settled[rank] {
+            continue
+        }
+
+        current.retain(|p| p != page);
+        let to = match (0..rank).rev().find(|r| settled[*r]) {
+            Some(r) => current.iter().position(|p| *p == sorted_list[r]).unwrap() + 1,
+            None => 0,
+        };
+        current.insert(to, *page);
+
+        settled[rank] = true;
+        moves.push(Move { page: *page, to });
+    }
+
+    moves
+}
+
+fn list_matches(list_one: &[isize], list_two: &[isize]) -> bool {
     let matching = list_one
         .iter()
         .zip(list_two.iter())
@@ -63,76 +240,554 @@ fn list_matches(list_one: &Vec<isize>, list_two: &Vec<isize>) -> bool {
     matching == list_one.len()
 }
 
+// a way to look up the page ordering rules
+trait OrderingRules {
+    fn has_rule(&self, before: isize, after: isize) -> bool;
+
+    // Less if a rule puts left before right, Greater if a rule puts right before left and Equal
+    // if no rule relates them. This isn't a total order, so it can't be used to sort an update.
+    fn compare(&self, left: isize, right: isize) -> Ordering {
+        if self.has_rule(left, right) {
+            Ordering::Less
+        } else if self.has_rule(right, left) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
 struct RuleComparer {
     rules: RuleMap,
 }
 
 impl RuleComparer {
-    fn new(file: &str) -> RuleComparer {
+    fn new(rules: RuleMap) -> RuleComparer {
         RuleComparer {
-            rules: process_rules(file),
+            rules,
         }
     }
 
-    fn compare(&self, left: isize, right: isize) -> Ordering {
-        if left == right {
-            return Ordering::Equal;
+}
+
+impl OrderingRules for RuleComparer {
+    fn has_rule(&self, before: isize, after: isize) -> bool {
+        self.rules
+            .get(&before)
+            .is_some_and(|afters| afters.contains(&after))
+    }
+}
+
+// the rules as an adjacency matrix, where bit `after` of row `before` is set when there is a
+// before|after rule
+struct RuleMatrix {
+    rows: [u128; MAX_PAGES],
+}
+
+impl RuleMatrix {
+    fn new(rules: &RuleMap) -> RuleMatrix {
+        let mut rows = [0u128; MAX_PAGES];
+
+        for (before, afters) in rules {
+            for after in afters {
+                rows[page_index(*before)] |= 1 << page_index(*after);
+            }
         }
 
-        match self.rules.get(&left) {
-            Some(v) => {
-                match v.contains(&right) {
-                    true => Ordering::Less,
-                    false => Ordering::Greater
-                }
+        RuleMatrix { rows }
+    }
+}
+
+impl OrderingRules for RuleMatrix {
+    fn has_rule(&self, before: isize, after: isize) -> bool {
+        self.rows[page_index(before)] & (1 << page_index(after)) != 0
+    }
+}
+
+fn page_index(page: isize) -> usize {
+    match usize::try_from(page) {
+        Ok(index) if index < MAX_PAGES => index,
+        _ => panic!("Page {page} should be a two digit number"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OrderError {
+    // the pages in a cycle of rules, starting and ending with the same page
+    Cycle(Vec<isize>),
+    // the pages that could all come next, so the update has more than one valid order
+    Ambiguous(Vec<isize>),
+}
+
+impl Display for OrderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderError::Cycle(pages) => write!(f, "the rules form a cycle {}", pages.iter().join(" -> ")),
+            OrderError::Ambiguous(pages) => write!(f, "any of {} could come next", pages.iter().join(", ")),
+        }
+    }
+}
+
+// the ordering rules between the pages of a single update. The rules as a whole contain cycles
+// but the rules restricted to the pages of one update should form a DAG with a single
+// topological order.
+#[derive(Debug)]
+struct DependencyGraph {
+    pages: Vec<isize>,
+    // every page mapped to the pages that have to come after it
+    edges: HashMap<isize, Vec<isize>>,
+}
+
+impl DependencyGraph {
+    fn new(ordering_rules: &impl OrderingRules, pages: &[isize]) -> DependencyGraph {
+        let edges = pages
+            .iter()
+            .map(|page| {
+                let afters = pages
+                    .iter()
+                    .filter(|after| ordering_rules.has_rule(*page, **after))
+                    .copied()
+                    .collect();
+
+                (*page, afters)
+            })
+            .collect();
+
+        DependencyGraph {
+            pages: pages.to_vec(),
+            edges,
+        }
+    }
+
+    // Kahn's algorithm. At every step exactly one page should have nothing left that has to come
+    // before it, otherwise the order is ambiguous or the rules have a cycle.
+    fn sort(&self) -> Result<Vec<isize>, OrderError> {
+        let mut in_degrees: HashMap<isize, usize> = self.pages.iter().map(|page| (*page, 0)).collect();
+        for after in self.edges.values().flatten() {
+            *in_degrees.get_mut(after).unwrap() += 1;
+        }
+
+        let mut sorted = Vec::with_capacity(self.pages.len());
+
+        while sorted.len() < self.pages.len() {
+            let ready = self.pages
+                .iter()
+                .filter(|page| in_degrees.get(page) == Some(&0))
+                .copied()
+                .collect::<Vec<isize>>();
+
+            let page = match ready[..] {
+                [page] => page,
+                [] => return Err(OrderError::Cycle(self.find_cycle(&in_degrees))),
+                _ => return Err(OrderError::Ambiguous(ready)),
+            };
+
+            in_degrees.remove(&page);
+            for after in &self.edges[&page] {
+                *in_degrees.get_mut(after).unwrap() -= 1;
+            }
+            sorted.push(page);
+        }
+
+        Ok(sorted)
+    }
+
+    // every page still in `remaining` has a page before it that is also remaining, so walking
+    // backwards from any of them has to end up going around a cycle
+    fn find_cycle(&self, remaining: &HashMap<isize, usize>) -> Vec<isize> {
+        let before = |page: isize| {
+            *self.pages
+                .iter()
+                .find(|before| remaining.contains_key(before) && self.edges[before].contains(&page))
+                .unwrap()
+        };
+
+        let mut path = vec![*self.pages.iter().find(|page| remaining.contains_key(page)).unwrap()];
+        loop {
+            let page = before(*path.last().unwrap());
+
+            if let Some(start) = path.iter().position(|p| *p == page) {
+                // the path was walked backwards, so flip it to follow the rules
+                let mut cycle = path[start..].to_vec();
+                cycle.push(page);
+                cycle.reverse();
+                return cycle
             }
-            None => Ordering::Equal
+            path.push(page);
         }
     }
 }
 
-fn process_rules(file: &str) -> RuleMap {
-    let contents = fs::read_to_string(file)
-        .expect("Should have been able to read the file");
+// every page that appears in a rule, in ascending order
+fn rule_pages(rules: &RuleMap) -> BTreeSet<isize> {
+    rules
+        .iter()
+        .flat_map(|(before, afters)| afters.iter().chain([before]))
+        .copied()
+        .collect()
+}
+
+// the rules that can be reached through a chain of other rules. In a cyclic rule set two rules can
+// each imply the other, so this says which rules could be removed one at a time, not all at once.
+fn redundant_rules(rules: &RuleMap) -> Vec<(isize, isize)> {
+    rules
+        .iter()
+        .flat_map(|(before, afters)| afters.iter().map(move |after| (*before, *after)))
+        .filter(|(before, after)| {
+            // breadth first search from before, skipping the rule itself
+            let mut seen = HashSet::from([*before]);
+            let mut queue = VecDeque::from([*before]);
 
-    let rule_pairs = contents
-        .split_whitespace()
-        .map(|string| {
-            string.splitn(2, '|').map(|x| x.parse::<isize>().unwrap())
+            while let Some(page) = queue.pop_front() {
+                for next in rules.get(&page).into_iter().flatten() {
+                    if page == *before && next == after {
+                        continue
+                    }
+                    if next == after {
+                        return true
+                    }
+                    if seen.insert(*next) {
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            false
         })
-        .map(|mut x| (x.next().unwrap(), x.next().unwrap()));
+        .sorted()
+        .collect()
+}
+
+// Tarjan's algorithm. Each component is sorted and the components are in the order Tarjan finds
+// them, which is reverse topological order.
+fn strongly_connected_components(rules: &RuleMap) -> Vec<Vec<isize>> {
+    #[derive(Default)]
+    struct Tarjan {
+        index: usize,
+        indices: HashMap<isize, usize>,
+        low_links: HashMap<isize, usize>,
+        stack: Vec<isize>,
+        on_stack: HashSet<isize>,
+        components: Vec<Vec<isize>>,
+    }
 
-    let mut rule_map: RuleMap = HashMap::new();
+    fn connect(tarjan: &mut Tarjan, rules: &RuleMap, page: isize) {
+        tarjan.indices.insert(page, tarjan.index);
+        tarjan.low_links.insert(page, tarjan.index);
+        tarjan.index += 1;
+        tarjan.stack.push(page);
+        tarjan.on_stack.insert(page);
 
-    for (left, right) in rule_pairs {
-        if rule_map.contains_key(&left) {
-            rule_map.get_mut(&left).unwrap().push(right);
-        } else {
-            rule_map.insert(left, vec![right]);
+        for after in rules.get(&page).into_iter().flatten() {
+            if !tarjan.indices.contains_key(after) {
+                connect(tarjan, rules, *after);
+                let low_link = tarjan.low_links[&page].min(tarjan.low_links[after]);
+                tarjan.low_links.insert(page, low_link);
+            } else if tarjan.on_stack.contains(after) {
+                let low_link = tarjan.low_links[&page].min(tarjan.indices[after]);
+                tarjan.low_links.insert(page, low_link);
+            }
+        }
+
+        if tarjan.low_links[&page] == tarjan.indices[&page] {
+            let mut component = Vec::new();
+            loop {
+                let member = tarjan.stack.pop().unwrap();
+                tarjan.on_stack.remove(&member);
+                component.push(member);
+                if member == page {
+                    break
+                }
+            }
+            component.sort();
+            tarjan.components.push(component);
+        }
+    }
+
+    let mut tarjan = Tarjan::default();
+    for page in rule_pages(rules) {
+        if !tarjan.indices.contains_key(&page) {
+            connect(&mut tarjan, rules, page);
         }
     }
 
-    rule_map
+    tarjan.components
 }
 
-fn process_lists(file: &str) -> Vec<Vec<isize>> {
+// the rules as a Graphviz graph, with the redundant rules drawn dashed
+fn rules_to_dot(rules: &RuleMap, redundant: &[(isize, isize)]) -> String {
+    let mut dot = String::from("digraph rules {\n");
+
+    for page in rule_pages(rules) {
+        dot.push_str(&format!("    {page};\n"));
+    }
+
+    for (before, afters) in rules.iter().sorted() {
+        for after in afters.iter().sorted() {
+            let style = match redundant.contains(&(*before, *after)) {
+                true => " [style=dashed]",
+                false => "",
+            };
+            dot.push_str(&format!("    {before} -> {after}{style};\n"));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PuzzleInput {
+    pub rules: RuleMap,
+    pub updates: Vec<Vec<isize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InputError {
+    // there is no blank line between the rules and the updates
+    MissingSeparator,
+    // line numbers start at 1 and count from the top of the file
+    InvalidRule { line: usize, text: String },
+    InvalidUpdate { line: usize, text: String },
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::MissingSeparator => write!(f, "there should be a blank line between the rules and the updates"),
+            InputError::InvalidRule { line, text } => write!(f, "line {line} should be a rule like 47|53 but is `{text}`"),
+            InputError::InvalidUpdate { line, text } => write!(f, "line {line} should be an update like 75,47,61 but is `{text}`"),
+        }
+    }
+}
+
+fn process_input(file: &str) -> PuzzleInput {
     let contents = fs::read_to_string(file)
         .expect("Should have been able to read the file");
 
-    contents
-        .split('\n')
-        .map(|list| {
-            list
-                .split(',')
-                .map(|n| n.parse::<isize>().unwrap())
-                .collect()
+    parse_input(&contents).unwrap_or_else(|error| panic!("Should have been able to parse {file}: {error}"))
+}
+
+// parse the puzzle input as given, the rules, a blank line, then the updates
+fn parse_input(contents: &str) -> Result<PuzzleInput, InputError> {
+    let lines = contents.lines().collect::<Vec<&str>>();
+    let separator = lines
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .ok_or(InputError::MissingSeparator)?;
+
+    let mut rules: RuleMap = HashMap::new();
+    for (i, line) in lines[..separator].iter().enumerate() {
+        let invalid = || InputError::InvalidRule { line: i + 1, text: line.to_string() };
+
+        let (before, after) = line.split_once('|').ok_or_else(invalid)?;
+        let before = parse_page(before).ok_or_else(invalid)?;
+        let after = parse_page(after).ok_or_else(invalid)?;
+
+        rules.entry(before).or_default().push(after);
+    }
+
+    // allow blank lines at the end of the file, but not between updates
+    let mut update_lines = &lines[separator + 1..];
+    while update_lines.last().is_some_and(|line| line.trim().is_empty()) {
+        update_lines = &update_lines[..update_lines.len() - 1];
+    }
+
+    let updates = update_lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            line.split(',')
+                .map(parse_page)
+                .collect::<Option<Vec<isize>>>()
+                .ok_or_else(|| InputError::InvalidUpdate { line: separator + i + 2, text: line.to_string() })
         })
-        .collect()
+        .collect::<Result<Vec<Vec<isize>>, InputError>>()?;
+
+    Ok(PuzzleInput { rules, updates })
+}
+
+// pages are numbers below MAX_PAGES
+fn parse_page(page: &str) -> Option<isize> {
+    page.trim()
+        .parse::<isize>()
+        .ok()
+        .filter(|page| (0..MAX_PAGES as isize).contains(page))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn rules(pairs: &[(isize, isize)]) -> RuleMap {
+        pairs.iter().into_group_map_by(|(before, _)| *before)
+            .into_iter()
+            .map(|(before, pairs)| (before, pairs.iter().map(|(_, after)| *after).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn sort_test() {
+        let rules = RuleMatrix::new(&rules(&[(47, 53), (97, 61), (97, 47), (75, 53), (61, 53), (97, 53), (75, 47), (97, 75), (47, 61), (75, 61)]));
+
+        assert_eq!(DependencyGraph::new(&rules, &[75, 97, 47, 61, 53]).sort(), Ok(vec![97, 75, 47, 61, 53]));
+        assert_eq!(DependencyGraph::new(&rules, &[61, 53, 47]).sort(), Ok(vec![47, 61, 53]));
+    }
+
+    #[test]
+    fn sort_errors_test() {
+        let rules = RuleComparer { rules: rules(&[(1, 2), (2, 3), (3, 1), (3, 4)]) };
+
+        let error = DependencyGraph::new(&rules, &[4, 1, 2, 3]).sort().unwrap_err();
+        assert_eq!(error, OrderError::Cycle(vec![3, 1, 2, 3]));
+        assert_eq!(error.to_string(), "the rules form a cycle 3 -> 1 -> 2 -> 3");
+
+        // 3 comes first, but nothing says if 1 or 4 comes after it
+        let error = DependencyGraph::new(&rules, &[1, 3, 4]).sort().unwrap_err();
+        assert_eq!(error, OrderError::Ambiguous(vec![1, 4]));
+        assert_eq!(error.to_string(), "any of 1, 4 could come next");
+
+        // sorting and explaining an update hand the error back instead of panicking
+        assert_eq!(sort_update(&rules, &[1, 3, 4]), Err(OrderError::Ambiguous(vec![1, 4])));
+        assert_eq!(explain_update(&rules, &[4, 1, 2, 3]), Err(OrderError::Cycle(vec![3, 1, 2, 3])));
+
+        let error = UpdateError { update: vec![1, 3, 4], error: OrderError::Ambiguous(vec![1, 4]) };
+        assert_eq!(error.to_string(), "update 1,3,4 can't be ordered: any of 1, 4 could come next");
+    }
+
+    #[test]
+    fn rule_matrix_test() {
+        let rules = process_input("inputs/day_five_input.txt").rules;
+        let rule_matrix = RuleMatrix::new(&rules);
+        let rule_comparer = RuleComparer::new(rules);
+
+        for (left, right) in (10..100).cartesian_product(10..100) {
+            assert_eq!(rule_matrix.compare(left, right), rule_comparer.compare(left, right));
+        }
+        assert!(rule_matrix.has_rule(14, 17));
+        assert!(!rule_matrix.has_rule(17, 14));
+    }
+
+    #[test]
+    fn explain_test() {
+        let rule_comparer = RuleComparer {
+            rules: rules(&[(47, 53), (97, 13), (97, 61), (97, 47), (75, 29), (61, 13), (75, 53), (29, 13), (97, 29), (53, 29), (61, 53), (97, 53), (61, 29), (47, 13), (75, 47), (97, 75), (47, 61), (75, 61), (47, 29), (75, 13), (53, 13)]),
+        };
+
+        assert_eq!(explain_update(&rule_comparer, &[75, 47, 61, 53, 29]), Ok(Explanation {
+            violations: vec![],
+            moves: vec![],
+        }));
+
+        assert_eq!(explain_update(&rule_comparer, &[75, 97, 47, 61, 53]), Ok(Explanation {
+            violations: vec![(97, 75)],
+            moves: vec![Move { page: 97, to: 0 }],
+        }));
+
+        assert_eq!(explain_update(&rule_comparer, &[97, 13, 75, 29, 47]), Ok(Explanation {
+            violations: vec![(75, 13), (29, 13), (47, 13), (47, 29)],
+            moves: vec![Move { page: 47, to: 3 }, Move { page: 13, to: 4 }],
+        }));
+    }
+
+    #[test]
+    fn minimal_moves_test() {
+        let apply = |list: &[isize], moves: &[Move]| {
+            let mut list = list.to_vec();
+            for Move { page, to } in moves {
+                list.retain(|p| p != page);
+                list.insert(*to, *page);
+            }
+            list
+        };
+
+        let sorted = [1, 2, 3, 4, 5, 6];
+        for list in [[6, 5, 4, 3, 2, 1], [2, 3, 4, 5, 6, 1], [1, 5, 2, 6, 3, 4], [4, 1, 5, 2, 6, 3]] {
+            let moves = minimal_moves(&list, &sorted);
+            assert_eq!(apply(&list, &moves), sorted);
+        }
+
+        assert_eq!(minimal_moves(&[6, 5, 4, 3, 2, 1], &sorted).len(), 5);
+        assert_eq!(minimal_moves(&[2, 3, 4, 5, 6, 1], &sorted), vec![Move { page: 1, to: 0 }]);
+        assert_eq!(minimal_moves(&[1, 5, 2, 6, 3, 4], &sorted).len(), 2);
+    }
+
+    #[test]
+    fn redundant_rules_test() {
+        let chain = rules(&[(1, 2), (2, 3), (1, 3), (3, 4), (1, 4)]);
+        assert_eq!(redundant_rules(&chain), vec![(1, 3), (1, 4)]);
+
+        // in a cycle a rule can be implied by going the long way around
+        let cycle = rules(&[(1, 2), (2, 1)]);
+        assert!(redundant_rules(&cycle).is_empty());
+        let cycle = rules(&[(1, 2), (2, 3), (3, 1), (1, 3)]);
+        assert_eq!(redundant_rules(&cycle), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn strongly_connected_components_test() {
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 4), (6, 1)]);
+
+        assert_eq!(strongly_connected_components(&rules), vec![vec![4, 5], vec![1, 2, 3], vec![6]]);
+    }
+
+    #[test]
+    fn rules_to_dot_test() {
+        let rules = rules(&[(2, 3), (1, 2), (1, 3)]);
+
+        assert_eq!(rules_to_dot(&rules, &redundant_rules(&rules)), concat!(
+            "digraph rules {\n",
+            "    1;\n",
+            "    2;\n",
+            "    3;\n",
+            "    1 -> 2;\n",
+            "    1 -> 3 [style=dashed];\n",
+            "    2 -> 3;\n",
+            "}\n",
+        ));
+    }
+
+    #[test]
+    fn rule_input_analysis_test() {
+        let rules = process_input("inputs/day_five_input.txt").rules;
+
+        // every page is tied into one big cycle, which is why the rules only form an order once
+        // they are restricted to the pages of an update
+        assert_eq!(strongly_connected_components(&rules).len(), 1);
+    }
+
+    #[test]
+    fn parse_input_test() {
+        let input = parse_input("47|53\r\n97|13\r\n97|61\r\n\r\n75,47,61\r\n97,61,53,29,13\r\n").unwrap();
+
+        assert_eq!(input, PuzzleInput {
+            rules: rules(&[(47, 53), (97, 13), (97, 61)]),
+            updates: vec![vec![75, 47, 61], vec![97, 61, 53, 29, 13]],
+        });
+    }
+
+    #[test]
+    fn parse_input_errors_test() {
+        assert_eq!(parse_input("47|53\n75,47,61"), Err(InputError::MissingSeparator));
+
+        let error = parse_input("47|53\n47-61\n\n75,47,61").unwrap_err();
+        assert_eq!(error, InputError::InvalidRule { line: 2, text: "47-61".to_string() });
+        assert_eq!(error.to_string(), "line 2 should be a rule like 47|53 but is `47-61`");
+
+        assert_eq!(
+            parse_input("47|153\n\n75,47,61").unwrap_err(),
+            InputError::InvalidRule { line: 1, text: "47|153".to_string() },
+        );
+        assert_eq!(
+            parse_input("47|53\n\n75,47,61\n75 47 61").unwrap_err(),
+            InputError::InvalidUpdate { line: 4, text: "75 47 61".to_string() },
+        );
+        assert_eq!(
+            parse_input("47|53\n\n75,47,61\n\n97,61,53\n").unwrap_err(),
+            InputError::InvalidUpdate { line: 4, text: "".to_string() },
+        );
+        assert_eq!(parse_input("47|53\n\n75,47,61\n\n\n").map(|input| input.updates), Ok(vec![vec![75, 47, 61]]));
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 5391);
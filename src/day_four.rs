@@ -1,5 +1,24 @@
-use std::collections::VecDeque;
+use std::collections::HashMap;
 use std::fs;
+use std::io;
+use std::io::Write;
+use crate::grid;
+use crate::grid::GridError;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_DIM: &str = "\x1b[2m";
+// added to a match color when more than one match covers a cell
+const ANSI_UNDERLINE: &str = "\x1b[4m";
+const ANSI_COLORS: [&str; 8] = [
+    "\x1b[1;31m",
+    "\x1b[1;32m",
+    "\x1b[1;33m",
+    "\x1b[1;34m",
+    "\x1b[1;35m",
+    "\x1b[1;36m",
+    "\x1b[1;91m",
+    "\x1b[1;92m",
+];
 
 const DIRECTION_OFFSETS: [(isize, isize); 8] = [
     (-1, -1),
@@ -14,183 +33,586 @@ const DIRECTION_OFFSETS: [(isize, isize); 8] = [
 
 #[derive(Debug)]
 struct CharMatrix {
-    chars: Vec<char>,
+    // the grid stored row by row, one byte per cell since the puzzle input is ascii
+    chars: Vec<u8>,
     pub width: isize,
     pub height: isize,
 }
 
 impl CharMatrix {
-    fn new(input: &str) -> CharMatrix {
-        CharMatrix {
-            chars: process_input(input),
-            width: input.find('\n').unwrap() as isize,
-            height: (input.chars().filter(|c| *c == '\n').count() + 1) as isize,
-        }
-    }
-
-    fn char_location_list(&self) -> VecDeque<((isize, isize), &char)> {
-        let mut list = VecDeque::new();
-
-        for x in 0..self.width {
-            for y in 0..self.height {
-                let c = self.get(x, y).unwrap();
-
-                list.push_back(((x, y), c));
-            }
-        }
+    fn new(input: &str) -> Result<CharMatrix, GridError> {
+        let grid = grid::parse_rows(input)?;
 
-        list
+        Ok(CharMatrix {
+            chars: process_input(&grid.rows),
+            width: grid.width,
+            height: grid.height,
+        })
     }
 
-    fn iter(&self) -> CharMatrixIterator {
+    fn iter(&self) -> CharMatrixIterator<'_> {
         CharMatrixIterator::new(self)
     }
 
-    fn get(&self, x: isize, y: isize) -> Option<&char> {
+    fn get(&self, x: isize, y: isize) -> Option<char> {
         if x < 0 || x >= self.width || y < 0 || y >= self.height {
             return None
         }
         let index = x + y * self.width;
-        self.chars.get(index as usize)
+        self.chars.get(index as usize).map(|c| *c as char)
     }
 
-    fn is_char_at(&self, c: &char, x: isize, y: isize) -> bool {
+    fn is_char_at(&self, c: char, x: isize, y: isize) -> bool {
         match self.get(x, y) {
             Some(x) => c == x,
             _ => false,
         }
     }
 
-    fn count_from_pattern(&self, patterns: &Vec<Vec<((isize, isize), char)>>, target_char: char)  -> isize {
+    // find every word from the dictionary in every direction from every cell. All the words are
+    // searched at once by walking a trie of the dictionary as we step away from the start cell.
+    fn search(&self, words: &[&str], options: SearchOptions) -> Vec<WordMatch> {
+        let trie = Trie::new(words);
+        let max_length = words.iter().map(|word| word.chars().count()).max().unwrap_or(0);
+
+        let mut matches = Vec::new();
+
+        for ((x, y), _c) in self.iter() {
+            for (i, direction) in DIRECTION_OFFSETS.iter().enumerate() {
+                let mut node = &trie;
+                let mut position = (x, y);
+
+                // wrapping around, a word would come back to the cell it started on after crossing
+                // the whole grid, so it can't be longer than the grid is wide or tall
+                let max_length = match (options.wraparound, direction.1) {
+                    (false, _) => max_length,
+                    (true, 0) => max_length.min(self.width as usize),
+                    (true, _) => max_length.min(self.height as usize),
+                };
+
+                for length in 1..=max_length {
+                    let c = match self.get_with_options(position, options) {
+                        Some(c) => c,
+                        None => break,
+                    };
+                    node = match node.children.get(&c) {
+                        Some(node) => node,
+                        None => break,
+                    };
+
+                    // a one letter word would otherwise be found once per direction
+                    if let Some(word) = &node.word {
+                        if length > 1 || i == 0 {
+                            matches.push(WordMatch {
+                                start: (x, y),
+                                direction: *direction,
+                                word: word.clone(),
+                            });
+                        }
+                    }
+
+                    position = (position.0 + direction.0, position.1 + direction.1);
+                }
+            }
+        }
+
+        matches
+    }
+
+    fn get_with_options(&self, position: (isize, isize), options: SearchOptions) -> Option<char> {
+        match options.wraparound {
+            true => self.get(position.0.rem_euclid(self.width), position.1.rem_euclid(self.height)),
+            false => self.get(position.0, position.1),
+        }
+    }
+
+    fn render_matches<H: Highlight>(&self, matches: &[H], style: RenderStyle, writer: &mut impl Write) -> io::Result<()> {
+        // the group of the first match to cover each cell and how many matches cover it
+        let mut covered: HashMap<(isize, isize), (usize, usize)> = HashMap::new();
+        for highlight in matches {
+            for (x, y) in highlight.cells() {
+                // wrapped around matches can run off the grid
+                let cell = (x.rem_euclid(self.width), y.rem_euclid(self.height));
+                covered.entry(cell).or_insert((highlight.group(), 0)).1 += 1;
+            }
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.get(x, y).unwrap();
+
+                match (style, covered.get(&(x, y))) {
+                    (RenderStyle::Dots, Some(_)) => write!(writer, "{c}")?,
+                    (RenderStyle::Dots, None) => write!(writer, ".")?,
+                    (RenderStyle::Color, Some((group, count))) => {
+                        let color = ANSI_COLORS[group % ANSI_COLORS.len()];
+                        let underline = if *count > 1 { ANSI_UNDERLINE } else { "" };
+                        write!(writer, "{color}{underline}{c}{ANSI_RESET}")?
+                    },
+                    (RenderStyle::Color, None) => write!(writer, "{ANSI_DIM}{c}{ANSI_RESET}")?,
+                }
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    // find every place any rotation or reflection of the stencil matches
+    fn find_stencil(&self, stencil: &Stencil) -> Vec<StencilMatch> {
+        let variants = stencil.variants();
+
         self.iter()
-            .filter(|((_x, _y), c)| {
-                **c == target_char
-            })
-            .map(|((x, y), _c)| {
-                patterns.iter()
-                    .map(|offset_list: &Vec<((isize, isize), char)>| {
-                        offset_list.iter()
+            .flat_map(|((x, y), _c)| {
+                variants.iter()
+                    .enumerate()
+                    .filter(move |(_, variant)| {
+                        variant.cells.iter()
                             .all(|((x_offset, y_offset), c)| {
-                                self.is_char_at(&c, x + x_offset, y + y_offset)
+                                self.is_char_at(*c, x + x_offset, y + y_offset)
                             })
                     })
-                    .filter(|b| *b )
-                    .count() as isize
+                    .map(move |(i, variant)| StencilMatch {
+                        origin: (x, y),
+                        variant: i,
+                        cells: variant.cells.iter()
+                            .map(|((x_offset, y_offset), _c)| (x + x_offset, y + y_offset))
+                            .collect(),
+                    })
+            })
+            .collect()
+    }
+}
+
+// a 2D pattern drawn as ascii art, one row per line, where `.` matches any character. For example
+// the X-MAS shape is
+//
+//     M.S
+//     .A.
+//     M.S
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stencil {
+    // the offset of every non wildcard cell from the top left of the stencil, kept sorted so equal
+    // stencils compare equal
+    pub cells: Vec<((isize, isize), char)>,
+}
+
+impl Stencil {
+    fn parse(art: &str) -> Stencil {
+        let cells = art
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_x, c)| *c != '.')
+                    .map(move |(x, c)| ((x as isize, y as isize), c))
+            })
+            .collect();
+
+        Stencil::new(cells)
+    }
+
+    // move the cells so the top left is at (0, 0) and sort them
+    fn new(mut cells: Vec<((isize, isize), char)>) -> Stencil {
+        let min_x = cells.iter().map(|((x, _y), _c)| *x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|((_x, y), _c)| *y).min().unwrap_or(0);
+
+        for ((x, y), _c) in cells.iter_mut() {
+            *x -= min_x;
+            *y -= min_y;
+        }
+        cells.sort_by_key(|((x, y), _c)| (*y, *x));
+
+        Stencil { cells }
+    }
+
+    // rotate a quarter turn clockwise
+    fn rotate(&self) -> Stencil {
+        Stencil::new(self.cells.iter().map(|((x, y), c)| ((-y, *x), *c)).collect())
+    }
+
+    // mirror left to right
+    fn reflect(&self) -> Stencil {
+        Stencil::new(self.cells.iter().map(|((x, y), c)| ((-x, *y), *c)).collect())
+    }
+
+    // every distinct rotation and reflection of the stencil, starting with the stencil itself
+    fn variants(&self) -> Vec<Stencil> {
+        let mut variants: Vec<Stencil> = Vec::new();
+
+        for start in [self.clone(), self.reflect()] {
+            let mut stencil = start;
+            for _ in 0..4 {
+                if !variants.contains(&stencil) {
+                    variants.push(stencil.clone());
+                }
+                stencil = stencil.rotate();
+            }
+        }
+
+        variants
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderStyle {
+    // matched cells keep their letter and everything else becomes `.`, like the puzzle examples
+    Dots,
+    // every cell is shown, with matched cells colored by the direction or variant of the match.
+    // Cells that more than one match covers are also underlined.
+    Color,
+}
+
+// anything that can be drawn on top of a CharMatrix by render_matches
+trait Highlight {
+    fn cells(&self) -> Vec<(isize, isize)>;
+
+    // matches in the same group get the same color
+    fn group(&self) -> usize;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StencilMatch {
+    // where the top left of the matching variant is
+    pub origin: (isize, isize),
+    // the index of the matching variant in Stencil::variants
+    pub variant: usize,
+    // every non wildcard cell the match covers
+    pub cells: Vec<(isize, isize)>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SearchOptions {
+    // let words run off one edge of the grid and continue from the opposite edge
+    pub wraparound: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WordMatch {
+    pub start: (isize, isize),
+    pub direction: (isize, isize),
+    pub word: String,
+}
+
+impl Highlight for StencilMatch {
+    fn cells(&self) -> Vec<(isize, isize)> {
+        self.cells.clone()
+    }
+
+    fn group(&self) -> usize {
+        self.variant
+    }
+}
+
+impl Highlight for WordMatch {
+    fn cells(&self) -> Vec<(isize, isize)> {
+        (0..self.word.chars().count() as isize)
+            .map(|distance| {
+                (self.start.0 + self.direction.0 * distance, self.start.1 + self.direction.1 * distance)
             })
-            .sum()
+            .collect()
+    }
+
+    fn group(&self) -> usize {
+        DIRECTION_OFFSETS.iter().position(|direction| *direction == self.direction).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Trie {
+    children: HashMap<char, Trie>,
+    // set when the path to this node spells a word from the dictionary
+    word: Option<String>,
+}
+
+impl Trie {
+    fn new(words: &[&str]) -> Trie {
+        let mut trie = Trie::default();
+        for word in words {
+            trie.insert(word);
+        }
+        trie
+    }
+
+    fn insert(&mut self, word: &str) {
+        let node = word
+            .chars()
+            .fold(self, |node, c| node.children.entry(c).or_default());
+
+        node.word = Some(word.to_string());
     }
 }
 
-struct CharMatrixIterator<'a>  {
-    char_location_list: VecDeque<((isize, isize), &'a char)>,
+// walks the grid row by row, working out each position from the index instead of building a list
+// of every cell up front
+struct CharMatrixIterator<'a> {
+    char_matrix: &'a CharMatrix,
+    index: usize,
 }
 
 impl<'a> CharMatrixIterator<'a> {
-    fn new(char_matrix: &CharMatrix) -> CharMatrixIterator {
+    fn new(char_matrix: &'a CharMatrix) -> CharMatrixIterator<'a> {
         CharMatrixIterator {
-            char_location_list: char_matrix.char_location_list()
+            char_matrix,
+            index: 0,
         }
     }
 }
 
-impl<'a> Iterator for CharMatrixIterator<'a> {
-    type Item = ((isize, isize), &'a char);
+impl Iterator for CharMatrixIterator<'_> {
+    type Item = ((isize, isize), char);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.char_location_list.pop_front()
+        let c = *self.char_matrix.chars.get(self.index)?;
+        let index = self.index as isize;
+        self.index += 1;
+
+        let width = self.char_matrix.width;
+        Some(((index % width, index / width), c as char))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.char_matrix.chars.len() - self.index;
+        (remaining, Some(remaining))
     }
 }
 
-fn process_input(input: &str) -> Vec<char> {
-    input
-        // filter out whitespace and collect into Vec<char>
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .collect::<Vec<char>>()
+fn process_input(rows: &[&str]) -> Vec<u8> {
+    rows
+        // join the rows together and collect into Vec<u8>
+        .iter()
+        .flat_map(|row| row.bytes())
+        .collect::<Vec<u8>>()
 }
 
-pub fn part_one() -> isize {
+// render where the matches for either part are, to the terminal or to a file
+pub fn run(options: &[String]) {
+    if !options.iter().any(|option| option == "--highlight") {
+        println!("Part one: {}", part_one());
+        println!("Part two: {}", part_two());
+        return
+    }
+
     let contents = fs::read_to_string("inputs/day_four_input.txt")
         .expect("Should have been able to read the file");
+    let m = CharMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
+
+    let style = match options.iter().any(|option| option == "--color") {
+        true => RenderStyle::Color,
+        false => RenderStyle::Dots,
+    };
+
+    let mut writer: Box<dyn Write> = match crate::option_value(options, "--output") {
+        Some(file) => Box::new(fs::File::create(file).expect("Should have been able to create the file")),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let rendered = match options.iter().any(|option| option == "--part-two") {
+        true => m.render_matches(&m.find_stencil(&x_mas_stencil()), style, &mut writer),
+        false => m.render_matches(&m.search(&["XMAS"], SearchOptions::default()), style, &mut writer),
+    };
+    rendered.expect("Should have been able to write the matches");
+}
 
-    let m = CharMatrix::new(&*contents);
-    let word = "XMAS";
-
-    let word_patterns= patterns_from_word(word);
+pub fn part_one() -> isize {
+    let contents = fs::read_to_string("inputs/day_four_input.txt")
+        .expect("Should have been able to read the file");
 
-    let first_letter = word.chars().next().unwrap();
+    let m = CharMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
 
-    let word_count= m.count_from_pattern(&word_patterns, first_letter);
+    let word_count = m.search(&["XMAS"], SearchOptions::default()).len() as isize;
 
     println!("Word count: {word_count}");
     word_count
 }
 
-fn patterns_from_word(word: &str) -> Vec<Vec<((isize, isize), char)>> {
-    let word_length = word.len();
-
-    DIRECTION_OFFSETS.iter()
-        .map(|(x, y)| {
-            (0..word_length)
-                .map(|n| n as isize)
-                .map(|distance| {
-                    (*x * distance, *y * distance)
-                })
-                .zip(word.chars())
-                .collect()
-        })
-        .collect()
-}
-
 pub fn part_two() -> isize {
     let contents = fs::read_to_string("inputs/day_four_input.txt")
         .expect("Should have been able to read the file");
 
-    let m = CharMatrix::new(&*contents);
-
-    let patterns = vec![
-        vec!(
-            ((-1,  1), 'M'),
-            ((-1, -1), 'M'),
-            (( 1,  1), 'S'),
-            (( 1, -1), 'S'),
-            (( 0,  0), 'A'),
-        ),
-        vec!(
-            ((-1,  1), 'M'),
-            (( 1,  1), 'M'),
-            ((-1, -1), 'S'),
-            (( 1, -1), 'S'),
-            (( 0,  0), 'A'),
-        ),
-        vec!(
-            (( 1,  1), 'M'),
-            (( 1,  -1), 'M'),
-            ((-1,  1), 'S'),
-            ((-1, -1), 'S'),
-            (( 0,  0), 'A'),
-        ),
-        vec!(
-            ((-1, -1), 'M'),
-            (( 1, -1), 'M'),
-            ((-1,  1), 'S'),
-            (( 1,  1), 'S'),
-            (( 0,  0), 'A'),
-        ),
-    ];
-
-    let center_letter = 'A';
-
-    let word_count= m.count_from_pattern(&patterns, center_letter);
+    let m = CharMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
+
+    let word_count = m.find_stencil(&x_mas_stencil()).len() as isize;
 
     println!("Word count: {word_count}");
     word_count
 }
 
+fn x_mas_stencil() -> Stencil {
+    Stencil::parse("
+        M.S
+        .A.
+        M.S
+    ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn line_endings_test() {
+        let m = CharMatrix::new("AB\r\nCD\r\n").unwrap();
+
+        assert_eq!((m.width, m.height), (2, 2));
+        assert_eq!(m.iter().map(|(_, c)| c).collect::<String>(), "ABCD");
+        assert!(CharMatrix::new("AB\nC\n").is_err());
+    }
+
+    #[test]
+    fn iter_test() {
+        let m = CharMatrix::new("AB\nCD\nEF").unwrap();
+
+        assert_eq!(m.iter().collect::<Vec<((isize, isize), char)>>(), vec![
+            ((0, 0), 'A'),
+            ((1, 0), 'B'),
+            ((0, 1), 'C'),
+            ((1, 1), 'D'),
+            ((0, 2), 'E'),
+            ((1, 2), 'F'),
+        ]);
+        assert_eq!(m.iter().skip(4).size_hint(), (2, Some(2)));
+    }
+
+    #[test]
+    fn search_test() {
+        let m = CharMatrix::new(EXAMPLE).unwrap();
+
+        assert_eq!(m.search(&["XMAS"], SearchOptions::default()).len(), 18);
+
+        let matches = m.search(&["XMAS", "SAMX", "MAS"], SearchOptions::default());
+        let count = |word: &str| matches.iter().filter(|m| m.word == word).count();
+        assert_eq!(count("XMAS"), 18);
+        assert_eq!(count("SAMX"), 18);
+        assert!(matches.contains(&WordMatch {
+            start: (5, 0),
+            direction: (1, 0),
+            word: "XMAS".to_string(),
+        }));
+    }
+
+    #[test]
+    fn search_prefix_test() {
+        let m = CharMatrix::new("ABC\nDEF\nGHI").unwrap();
+
+        let matches = m.search(&["A", "AB", "ABC", "AEI"], SearchOptions::default());
+        let words = matches.iter().map(|m| m.word.as_str()).collect::<Vec<&str>>();
+        assert_eq!(words, vec!["A", "AB", "ABC", "AEI"]);
+    }
+
+    #[test]
+    fn search_wraparound_test() {
+        let m = CharMatrix::new("ABC\nDEF\nGHI").unwrap();
+
+        assert!(m.search(&["CA", "GA", "IA"], SearchOptions::default()).is_empty());
+
+        let matches = m.search(&["CA", "GA", "IA"], SearchOptions { wraparound: true });
+        assert_eq!(matches, vec![
+            WordMatch { start: (2, 0), direction: (1, 0), word: "CA".to_string() },
+            WordMatch { start: (0, 2), direction: (0, 1), word: "GA".to_string() },
+            WordMatch { start: (2, 2), direction: (1, 1), word: "IA".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn search_wraparound_revisit_test() {
+        let wraparound = SearchOptions { wraparound: true };
+
+        let m = CharMatrix::new("A").unwrap();
+        assert!(m.search(&["AA"], wraparound).is_empty());
+        assert_eq!(m.search(&["A"], wraparound).len(), 1);
+
+        // across the 3 wide rows a word can be 3 letters long, but down the 2 tall columns ABA would
+        // come back to its first A
+        let m = CharMatrix::new("ABC\nBCA").unwrap();
+        let matches = m.search(&["ABCA", "ABC", "ABA"], wraparound);
+        let count = |word: &str| matches.iter().filter(|m| m.word == word).count();
+        assert_eq!(count("ABCA"), 0);
+        assert_eq!(count("ABA"), 0);
+        assert_eq!(count("ABC"), 2);
+    }
+
+    #[test]
+    fn stencil_variants_test() {
+        let x_mas = Stencil::parse("M.S\n.A.\nM.S");
+
+        assert_eq!(x_mas.cells, vec![((0, 0), 'M'), ((2, 0), 'S'), ((1, 1), 'A'), ((0, 2), 'M'), ((2, 2), 'S')]);
+        assert_eq!(x_mas.variants().len(), 4);
+        assert_eq!(x_mas.rotate().rotate().rotate().rotate(), x_mas);
+
+        // an L shape has no symmetry so it has all 8 variants
+        let l_shape = Stencil::parse("
+            X.
+            X.
+            XY
+        ");
+        assert_eq!(l_shape.variants().len(), 8);
+        assert_eq!(l_shape.rotate(), Stencil::parse("XXX\nY.."));
+    }
+
+    #[test]
+    fn find_stencil_test() {
+        let m = CharMatrix::new(EXAMPLE).unwrap();
+        let x_mas = Stencil::parse("M.S\n.A.\nM.S");
+
+        let matches = m.find_stencil(&x_mas);
+        assert_eq!(matches.len(), 9);
+        assert!(matches.contains(&StencilMatch {
+            origin: (1, 0),
+            variant: 0,
+            cells: vec![(1, 0), (3, 0), (2, 1), (1, 2), (3, 2)],
+        }));
+    }
+
+    #[test]
+    fn render_dots_test() {
+        let m = CharMatrix::new("MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX").unwrap();
+
+        let mut rendered = Vec::new();
+        m.render_matches(&m.search(&["XMAS"], SearchOptions::default()), RenderStyle::Dots, &mut rendered).unwrap();
+
+        assert_eq!(String::from_utf8(rendered).unwrap(), "\
+            ....XXMAS.\n\
+            .SAMXM....\n\
+            ......A...\n\
+            .......S..\n");
+    }
+
+    #[test]
+    fn render_color_test() {
+        let m = CharMatrix::new("XMAS\nSAMX").unwrap();
+        let matches = m.search(&["XMAS", "XM"], SearchOptions::default());
+
+        let mut rendered = Vec::new();
+        m.render_matches(&matches, RenderStyle::Color, &mut rendered).unwrap();
+
+        // the top row runs right (group 6) and the bottom row runs left (group 1). XM is found
+        // inside both, so its cells are underlined.
+        let right = ANSI_COLORS[6];
+        let left = ANSI_COLORS[1];
+        let both = ANSI_UNDERLINE;
+        assert_eq!(String::from_utf8(rendered).unwrap(), format!("\
+            {right}{both}X{ANSI_RESET}{right}{both}M{ANSI_RESET}{right}A{ANSI_RESET}{right}S{ANSI_RESET}\n\
+            {left}S{ANSI_RESET}{left}A{ANSI_RESET}{left}{both}M{ANSI_RESET}{left}{both}X{ANSI_RESET}\n"));
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 2593);
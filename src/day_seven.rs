@@ -1,5 +1,7 @@
 use std::collections::VecDeque;
 use std::fs;
+use std::time::Instant;
+use rayon::prelude::*;
 
 pub fn part_one() -> isize {
     let calibration_tests = build_calibration_tests("inputs/day_seven_input.txt");
@@ -30,24 +32,185 @@ pub fn part_two() -> isize {
     calibration_total
 }
 
+pub fn run(options: &[String]) {
+    if options.iter().any(|option| option == "--bench") {
+        benchmark();
+    }
+
+    if options.iter().any(|option| option == "--solutions") {
+        let operations = match options.iter().any(|option| option == "--part-one") {
+            true => vec![Operation::Add, Operation::Multiply],
+            false => vec![Operation::Add, Operation::Multiply, Operation::Concatenate],
+        };
+        let all = options.iter().any(|option| option == "--all");
+        print_solutions(&build_calibration_tests("inputs/day_seven_input.txt"), &operations, all);
+        return
+    }
+
+    println!("Part one: {}", part_one());
+    println!("Part two: {}", part_two());
+}
+
+// time building the whole tree of operations against working backwards from the answer
+pub fn benchmark() {
+    let operations = [Operation::Add, Operation::Multiply, Operation::Concatenate];
+
+    let now = Instant::now();
+    let total = run_calibration_tests_by_tree(build_calibration_tests("inputs/day_seven_input.txt"), &operations);
+    println!("operation tree: {total} in {:.2?}", now.elapsed());
+
+    let now = Instant::now();
+    let total = run_calibration_tests(build_calibration_tests("inputs/day_seven_input.txt"), &operations);
+    println!("backward solver: {total} in {:.2?}", now.elapsed());
+}
+
+// print how every equation that can be solved is solved, and how many ways there are to solve it
+fn print_solutions(calibration_tests: &[CalibrationTest], operations: &[Operation], all: bool) {
+    for test in calibration_tests {
+        let Some(solution) = test.first_solution(operations) else {
+            continue
+        };
+
+        let count = test.count_solutions(operations);
+        let noun = if count == 1 { "solution" } else { "solutions" };
+        println!("{}: {} ({count} {noun})", test.answer, test.format_solution(&solution));
+
+        if all && count > 1 {
+            for solution in test.solutions(operations) {
+                println!("    {}", test.format_solution(&solution));
+            }
+        }
+    }
+}
+
 fn build_calibration_tests(file: &str) -> Vec<CalibrationTest> {
     let contents = fs::read_to_string(file)
         .expect("Should have been able to read the file");
 
+    parse_calibration_tests(&contents)
+}
+
+fn parse_calibration_tests(contents: &str) -> Vec<CalibrationTest> {
     contents
-        .split('\n')
-        .map(|x| CalibrationTest::new(x))
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(CalibrationTest::new)
         .collect()
 }
 
-fn run_calibration_tests(mut calibration_tests: Vec<CalibrationTest>, operations: &[Operation]) -> isize {
+fn run_calibration_tests(calibration_tests: Vec<CalibrationTest>, operations: &[Operation]) -> isize {
+    calibration_tests
+        // the tests are independent of each other so use rayon to solve them in parallel
+        .par_iter()
+        .filter(|test| can_solve(test.answer, &test.test_values, operations))
+        .map(|test| test.answer)
+        .sum()
+}
+
+// work backwards from the answer, undoing the operations on the last value and trying to solve
+// what is left, which cuts off every branch an operation can't be undone on
+fn can_solve(answer: isize, values: &[isize], operations: &[Operation]) -> bool {
+    match values {
+        [] => false,
+        [first] => *first == answer,
+        [rest @ .., last] => operations.iter().any(|operation| {
+            is_zeroed(operation, answer, *last)
+                || operation.undo(answer, *last).is_some_and(|remaining| can_solve(remaining, rest, operations))
+        }),
+    }
+}
+
+// multiplying the last value, a zero, into an answer of zero can't be undone since the values
+// before it could have come to anything. Working forwards instead, every way of combining them
+// gives zero once it is multiplied by zero, so that branch always solves the equation.
+fn is_zeroed(operation: &Operation, answer: isize, last: isize) -> bool {
+    *operation == Operation::Multiply && last == 0 && answer == 0
+}
+
+// every sequence of operations of a length, in the order the backward solver finds them
+fn every_sequence(length: usize, operations: &[Operation]) -> Vec<Vec<Operation>> {
+    match length {
+        0 => vec![Vec::new()],
+        _ => operations
+            .iter()
+            .flat_map(|operation| {
+                every_sequence(length - 1, operations)
+                    .into_iter()
+                    .map(|mut sequence| {
+                        sequence.push(*operation);
+                        sequence
+                    })
+            })
+            .collect(),
+    }
+}
+
+// the operations, in order, of the first way found to solve the equation
+fn first_solution(answer: isize, values: &[isize], operations: &[Operation]) -> Option<Vec<Operation>> {
+    match values {
+        [] => None,
+        [first] => (*first == answer).then(Vec::new),
+        [rest @ .., last] => operations.iter().find_map(|operation| {
+            let mut solution = match is_zeroed(operation, answer, *last) {
+                true => vec![operations[0]; rest.len() - 1],
+                false => first_solution(operation.undo(answer, *last)?, rest, operations)?,
+            };
+            solution.push(*operation);
+            Some(solution)
+        }),
+    }
+}
+
+// every way to solve the equation
+fn solutions(answer: isize, values: &[isize], operations: &[Operation]) -> Vec<Vec<Operation>> {
+    match values {
+        [] => Vec::new(),
+        [first] if *first == answer => vec![Vec::new()],
+        [_] => Vec::new(),
+        [rest @ .., last] => operations
+            .iter()
+            .flat_map(|operation| {
+                let solutions = match is_zeroed(operation, answer, *last) {
+                    true => every_sequence(rest.len() - 1, operations),
+                    false => operation.undo(answer, *last)
+                        .map(|remaining| solutions(remaining, rest, operations))
+                        .unwrap_or_default(),
+                };
+
+                solutions.into_iter().map(|mut solution| {
+                    solution.push(*operation);
+                    solution
+                })
+            })
+            .collect(),
+    }
+}
+
+fn count_solutions(answer: isize, values: &[isize], operations: &[Operation]) -> usize {
+    match values {
+        [] => 0,
+        [first] => usize::from(*first == answer),
+        [rest @ .., last] => operations
+            .iter()
+            .map(|operation| match is_zeroed(operation, answer, *last) {
+                true => operations.len().pow(rest.len() as u32 - 1),
+                false => operation.undo(answer, *last)
+                    .map_or(0, |remaining| count_solutions(remaining, rest, operations)),
+            })
+            .sum(),
+    }
+}
+
+// the original solver, kept to compare against in the benchmark
+fn run_calibration_tests_by_tree(calibration_tests: Vec<CalibrationTest>, operations: &[Operation]) -> isize {
     calibration_tests
-        .iter_mut()
+        .iter()
         .map(|test| {
             // build a tree of all the possible operation combinations
-            let first_value = test.test_values.pop_front().unwrap();
+            let mut test_values = VecDeque::from(test.test_values.clone());
+            let first_value = test_values.pop_front().unwrap();
             let mut tree_head = OperationNode::new(None, first_value);
-            tree_head.build_children(test.test_values.clone(), operations);
+            tree_head.build_children(test_values, operations);
 
             // calculate all the possible totals of every operation combination
             let mut totals_list = Vec::<isize>::new();
@@ -63,7 +226,7 @@ fn run_calibration_tests(mut calibration_tests: Vec<CalibrationTest>, operations
 
 struct CalibrationTest {
     pub answer: isize,
-    pub test_values: VecDeque<isize>,
+    pub test_values: Vec<isize>,
 }
 
 impl CalibrationTest {
@@ -85,6 +248,27 @@ impl CalibrationTest {
             test_values,
         }
     }
+
+    fn first_solution(&self, operations: &[Operation]) -> Option<Vec<Operation>> {
+        first_solution(self.answer, &self.test_values, operations)
+    }
+
+    fn solutions(&self, operations: &[Operation]) -> Vec<Vec<Operation>> {
+        solutions(self.answer, &self.test_values, operations)
+    }
+
+    fn count_solutions(&self, operations: &[Operation]) -> usize {
+        count_solutions(self.answer, &self.test_values, operations)
+    }
+
+    // the values with the operations between them, for example `81 + 40 * 27`
+    fn format_solution(&self, solution: &[Operation]) -> String {
+        let mut formatted = self.test_values[0].to_string();
+        for (operation, value) in solution.iter().zip(&self.test_values[1..]) {
+            formatted.push_str(&format!(" {} {value}", operation.symbol()));
+        }
+        formatted
+    }
 }
 
 #[derive(Debug)]
@@ -142,7 +326,7 @@ impl OperationNode {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Operation {
     Add,
     Multiply,
@@ -157,6 +341,33 @@ impl Operation {
             Operation::Concatenate => concatenate(lhs, rhs),
         }
     }
+
+    fn symbol(&self) -> &str {
+        match self {
+            Operation::Add => "+",
+            Operation::Multiply => "*",
+            Operation::Concatenate => "||",
+        }
+    }
+
+    // the lhs that gives the total when the operation is performed with rhs, if there is one.
+    // Multiplying by zero gives zero whatever the lhs was, so it can't be undone, see is_zeroed
+    fn undo(&self, total: isize, rhs: isize) -> Option<isize> {
+        match self {
+            Operation::Add if total >= rhs => Some(total - rhs),
+            Operation::Multiply if rhs != 0 && total % rhs == 0 => Some(total / rhs),
+            Operation::Concatenate => {
+                // an rhs too long to shift by can't have been concatenated onto anything
+                let shift = 10_isize.checked_pow(digit_count(rhs))?;
+                (total >= rhs && total % shift == rhs).then(|| total / shift)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn digit_count(value: isize) -> u32 {
+    value.checked_ilog10().unwrap_or(0) + 1
 }
 
 fn concatenate(lhs: isize, rhs: isize) -> isize {
@@ -169,6 +380,113 @@ fn concatenate(lhs: isize, rhs: isize) -> isize {
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20";
+
+    #[test]
+    fn undo_test() {
+        assert_eq!(Operation::Add.undo(29, 19), Some(10));
+        assert_eq!(Operation::Add.undo(19, 19), Some(0));
+        assert_eq!(Operation::Add.undo(18, 19), None);
+        assert_eq!(Operation::Multiply.undo(190, 19), Some(10));
+        assert_eq!(Operation::Multiply.undo(191, 19), None);
+        assert_eq!(Operation::Multiply.undo(0, 19), Some(0));
+        assert_eq!(Operation::Multiply.undo(0, 0), None);
+        assert_eq!(Operation::Concatenate.undo(156, 6), Some(15));
+        assert_eq!(Operation::Concatenate.undo(1510, 10), Some(15));
+        assert_eq!(Operation::Concatenate.undo(156, 56), Some(1));
+        assert_eq!(Operation::Concatenate.undo(156, 7), None);
+        assert_eq!(Operation::Concatenate.undo(6, 6), Some(0));
+        assert_eq!(Operation::Concatenate.undo(50, 0), Some(5));
+        assert_eq!(Operation::Concatenate.undo(isize::MAX, 1_000_000_000_000_000_000), None);
+    }
+
+    #[test]
+    fn zero_values_test() {
+        let operations = [Operation::Add, Operation::Multiply, Operation::Concatenate];
+
+        // the operations the tree solver finds for every total the values can come to
+        let tree_solutions = |answer: isize, values: &[isize]| {
+            let mut tree_head = OperationNode::new(None, values[0]);
+            tree_head.build_children(VecDeque::from(values[1..].to_vec()), &operations);
+
+            let mut totals_list = Vec::new();
+            tree_head.calculate_totals(0, &mut totals_list);
+            totals_list.iter().filter(|total| **total == answer).count()
+        };
+
+        for values in [vec![0, 5], vec![0, 0], vec![5, 0], vec![3, 4, 0], vec![0, 2, 0, 7], vec![10, 0, 1]] {
+            for answer in 0..=120 {
+                let count = tree_solutions(answer, &values);
+
+                assert_eq!(can_solve(answer, &values, &operations), count > 0, "{answer}: {values:?}");
+                assert_eq!(count_solutions(answer, &values, &operations), count, "{answer}: {values:?}");
+                assert_eq!(solutions(answer, &values, &operations).len(), count, "{answer}: {values:?}");
+
+                if let Some(solution) = first_solution(answer, &values, &operations) {
+                    let total = solution.iter()
+                        .zip(&values[1..])
+                        .fold(values[0], |total, (operation, value)| operation.perform(total, *value));
+                    assert_eq!(total, answer, "{answer}: {values:?}");
+                }
+            }
+        }
+
+        let calibration_tests = parse_calibration_tests("5: 0 5\n0: 0 0\n0: 4 2 0");
+        assert_eq!(calibration_tests.iter().map(|test| test.count_solutions(&operations)).collect::<Vec<_>>(), vec![2, 3, 3]);
+        assert_eq!(calibration_tests[2].first_solution(&operations), Some(vec![Operation::Add, Operation::Multiply]));
+    }
+
+    #[test]
+    fn backward_solver_test() {
+        let part_one = [Operation::Add, Operation::Multiply];
+        let part_two = [Operation::Add, Operation::Multiply, Operation::Concatenate];
+
+        assert_eq!(run_calibration_tests(parse_calibration_tests(EXAMPLE), &part_one), 3749);
+        assert_eq!(run_calibration_tests(parse_calibration_tests(EXAMPLE), &part_two), 11387);
+        assert_eq!(run_calibration_tests_by_tree(parse_calibration_tests(EXAMPLE), &part_one), 3749);
+        assert_eq!(run_calibration_tests_by_tree(parse_calibration_tests(EXAMPLE), &part_two), 11387);
+    }
+
+    #[test]
+    fn solutions_test() {
+        let operations = [Operation::Add, Operation::Multiply, Operation::Concatenate];
+        let calibration_tests = parse_calibration_tests(EXAMPLE);
+
+        let formatted = calibration_tests.iter()
+            .map(|test| test.first_solution(&operations).map(|solution| test.format_solution(&solution)))
+            .collect::<Vec<_>>();
+        assert_eq!(formatted, vec![
+            Some("10 * 19".to_string()),
+            Some("81 * 40 + 27".to_string()),
+            None,
+            Some("15 || 6".to_string()),
+            Some("6 * 8 || 6 * 15".to_string()),
+            None,
+            Some("17 || 8 + 14".to_string()),
+            None,
+            Some("11 + 6 * 16 + 20".to_string()),
+        ]);
+
+        let counts = calibration_tests.iter()
+            .map(|test| test.count_solutions(&operations))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 2, 0, 1, 1, 0, 1, 0, 1]);
+
+        let solutions = calibration_tests[1].solutions(&operations);
+        assert_eq!(solutions, vec![
+            vec![Operation::Multiply, Operation::Add],
+            vec![Operation::Add, Operation::Multiply],
+        ]);
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 3_312_271_365_652);
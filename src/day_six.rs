@@ -1,16 +1,22 @@
 use std::cmp::PartialEq;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::fs;
-use std::time::Instant;
+use std::sync::Arc;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
 use rayon::prelude::*;
+use crate::grid;
+use crate::grid::GridError;
 
 pub fn part_one() -> isize {
     let contents = fs::read_to_string("inputs/day_six_input.txt")
         .expect("Should have been able to read the file");
 
     // set up a patrol matrix that holds all the data about the patrol environment
-    let mut patrol_matrix = PatrolMatrix::new(&*contents);
+    let mut patrol_matrix = PatrolMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
 
     let now = Instant::now();
 
@@ -29,94 +35,819 @@ pub fn part_two() -> isize {
         .expect("Should have been able to read the file");
 
     // set up a patrol matrix that holds all the data about the patrol environment
-    let patrol_matrix = PatrolMatrix::new(&*contents);
+    let patrol_matrix = PatrolMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
 
     let now = Instant::now();
 
-    let loop_count = patrol_matrix.iter()
-        // first generate every possible new object placement and collect them into a vec
+    // an obstacle can only change the patrol if it is somewhere the guard would have walked
+    let candidates = path_candidates(&patrol_matrix);
+    let loop_count = find_loop_obstacles_by_jumping(&patrol_matrix, candidates).len();
 
-        // get all the empty squares
-        .filter(|(_, p)| {
-            *p == PatrolObject::Unseen
+    let elapsed = now.elapsed();
+
+    println!("There are {loop_count} loops.\nFound in {:.2?}.", elapsed);
+
+    loop_count as isize
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_RED: &str = "\x1b[1;31m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_CLEAR: &str = "\x1b[H\x1b[2J";
+
+pub fn run(options: &[String]) {
+    if options.iter().any(|option| option == "--bench") {
+        benchmark();
+    }
+
+    if options.iter().any(|option| option == "--replay") {
+        let patrol_matrix = patrol_matrix_from_options(options);
+        let mut replay_options = match ReplayOptions::from_options(options) {
+            Ok(replay_options) => replay_options,
+            Err(error) => {
+                eprintln!("{error}");
+                return
+            }
+        };
+
+        // without an obstacle of its own, part two replays the first obstacle that causes a loop
+        if replay_options.added_obstacle.is_none() && options.iter().any(|option| option == "--part-two") {
+            let mut loop_obstacles = find_loop_obstacles_by_jumping(&patrol_matrix, path_candidates(&patrol_matrix));
+            loop_obstacles.sort_by_key(|(x, y)| (*y, *x));
+            replay_options.added_obstacle = loop_obstacles.first().copied();
+        }
+
+        replay(patrol_matrix, &replay_options);
+        return
+    }
+
+    if let Some(file) = crate::option_value(options, "--image") {
+        let scale = crate::option_value(options, "--scale")
+            .map(|scale| scale.parse::<usize>().expect("--scale should be a number"))
+            .unwrap_or(4);
+
+        export_image(&patrol_matrix_from_options(options), file, scale);
+        return
+    }
+
+    if options.iter().any(|option| option == "--stats" || option == "--path") {
+        let mut patrol_matrix = patrol_matrix_from_options(options);
+        if let Some(obstacle) = crate::option_value(options, "--obstacle") {
+            patrol_matrix = patrol_matrix.with_obstacle(parse_pair(obstacle, ','));
+        }
+        patrol_matrix.run_patrol();
+
+        if options.iter().any(|option| option == "--path") {
+            print!("{}", patrol_matrix.render_path());
+        }
+        if options.iter().any(|option| option == "--stats") {
+            println!("{}", patrol_matrix.stats());
+        }
+        return
+    }
+
+    if ["--input", "--turn", "--collision"].iter().any(|name| crate::option_value(options, name).is_some()) {
+        explore(patrol_matrix_from_options(options));
+        return
+    }
+
+    println!("Part one: {}", part_one());
+    println!("Part two: {}", part_two());
+}
+
+// the puzzle input, or the map given with --input, with the turn and collision rules from the options
+fn patrol_matrix_from_options(options: &[String]) -> PatrolMatrix {
+    let file = crate::option_value(options, "--input").unwrap_or("inputs/day_six_input.txt");
+    let contents = fs::read_to_string(file)
+        .expect("Should have been able to read the file");
+    let mut patrol_matrix = PatrolMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
+
+    if let Some(name) = crate::option_value(options, "--turn") {
+        patrol_matrix.turn_rule = turn_rule_by_name(name)
+            .unwrap_or_else(|| panic!("{name} should be one of right, left, reverse or alternating"));
+    }
+    if let Some(name) = crate::option_value(options, "--collision") {
+        patrol_matrix.collision = collision_by_name(name)
+            .unwrap_or_else(|| panic!("{name} should be one of pass, block or stop"));
+    }
+
+    patrol_matrix
+}
+
+// run both parts against a variant of the puzzle
+fn explore(patrol_matrix: PatrolMatrix) {
+    let mut patrolled = patrol_matrix.clone();
+    patrolled.run_patrol();
+
+    println!("The {} guards saw {}.", patrol_matrix.guards.len(), patrolled.count_seen());
+    if patrolled.ended_in_loop {
+        println!("The patrol ended in a loop.");
+    }
+    for (x, y) in &patrolled.collisions {
+        println!("Guards collided at ({x}, {y}).");
+    }
+
+    let loop_count = find_loop_obstacles_by_jumping(&patrol_matrix, empty_candidates(&patrol_matrix)).len();
+    println!("There are {loop_count} loops.");
+}
+
+// time the original part two, which tries every empty cell and scans the list of turns for loops,
+// against trying only cells on the patrol path, checking for loops with the state bitset and
+// jumping from turn to turn with the jump table
+pub fn benchmark() {
+    let contents = fs::read_to_string("inputs/day_six_input.txt")
+        .expect("Should have been able to read the file");
+    let patrol_matrix = PatrolMatrix::new(&contents)
+        .expect("Should have been able to parse the grid");
+
+    let time = |name: &str, loop_detection: LoopDetection, candidates: Vec<(isize, isize)>| {
+        let patrol_matrix = PatrolMatrix {
+            loop_detection,
+            ..patrol_matrix.clone()
+        };
+
+        let now = Instant::now();
+        let candidate_count = candidates.len();
+        let loop_count = find_loop_obstacles(&patrol_matrix, candidates).len();
+        println!("{name}: {loop_count} loops from {candidate_count} candidates in {:.2?}", now.elapsed());
+    };
+
+    time("every empty cell, turn history", LoopDetection::TurnHistory, empty_candidates(&patrol_matrix));
+    time("every empty cell, state bitset", LoopDetection::StateBitset, empty_candidates(&patrol_matrix));
+    time("patrol path, turn history", LoopDetection::TurnHistory, path_candidates(&patrol_matrix));
+    time("patrol path, state bitset", LoopDetection::StateBitset, path_candidates(&patrol_matrix));
+
+    let now = Instant::now();
+    let candidates = path_candidates(&patrol_matrix);
+    let candidate_count = candidates.len();
+    let loop_count = find_loop_obstacles_by_jumping(&patrol_matrix, candidates).len();
+    println!("patrol path, jump table: {loop_count} loops from {candidate_count} candidates in {:.2?}", now.elapsed());
+}
+
+struct ReplayOptions {
+    fps: f64,
+    // only draw a frame when the guard turns instead of on every step
+    turns_only: bool,
+    // the width and height of the part of the map around the guard to draw
+    viewport: Option<(isize, isize)>,
+    added_obstacle: Option<(isize, isize)>,
+}
+
+impl ReplayOptions {
+    fn from_options(options: &[String]) -> Result<ReplayOptions, String> {
+        let fps = match crate::option_value(options, "--fps") {
+            // a frame takes 1 / fps seconds, so anything that isn't a positive finite number can't
+            // be turned into a frame time
+            Some(fps) => match fps.parse::<f64>() {
+                Ok(fps) if fps.is_finite() && fps > 0.0 => fps,
+                _ => return Err(format!("--fps should be a positive number but is {fps}")),
+            },
+            None => 30.0,
+        };
+
+        Ok(ReplayOptions {
+            fps,
+            turns_only: options.iter().any(|option| option == "--turns"),
+            viewport: crate::option_value(options, "--viewport").map(|viewport| parse_pair(viewport, 'x')),
+            added_obstacle: crate::option_value(options, "--obstacle").map(|obstacle| parse_pair(obstacle, ',')),
         })
+    }
+}
+
+fn parse_pair(value: &str, separator: char) -> (isize, isize) {
+    let (first, second) = value.split_once(separator)
+        .unwrap_or_else(|| panic!("{value} should be two numbers separated by '{separator}'"));
+
+    (first.trim().parse().unwrap(), second.trim().parse().unwrap())
+}
+
+// step the guard through the patrol, drawing a frame to the terminal after every step or turn, and
+// once the guard is caught in a loop highlight the cells the loop goes through
+fn replay(mut patrol_matrix: PatrolMatrix, replay_options: &ReplayOptions) {
+    if let Some(added_obstacle) = replay_options.added_obstacle {
+        patrol_matrix = patrol_matrix.with_obstacle(added_obstacle);
+    }
+
+    let frame_time = Duration::from_secs_f64(1.0 / replay_options.fps);
+    let mut stdout = io::stdout().lock();
+
+    // every position the guard has been on, and where in that path each turn happened
+    let mut path = Vec::new();
+    let mut turn_steps = HashMap::new();
+    let mut cycle = HashSet::new();
+
+    let mut draw = |patrol_matrix: &PatrolMatrix, center: (isize, isize), cycle: &HashSet<(isize, isize)>| {
+        let frame = render_frame(patrol_matrix, center, replay_options.viewport, replay_options.added_obstacle, cycle);
+        write!(stdout, "{ANSI_CLEAR}{frame}").expect("Should have been able to draw the frame");
+        stdout.flush().expect("Should have been able to draw the frame");
+        thread::sleep(frame_time);
+    };
+
+    // the replay follows the first guard still on patrol
+    while let Some(guard) = patrol_matrix.guards.first() {
+        let (position, turn_count) = (guard.position, guard.turn_count);
+        let state = guard.state(patrol_matrix.turn_rule);
+        if path.is_empty() {
+            draw(&patrol_matrix, position, &cycle);
+        }
+
+        path.push(position);
+        patrol_matrix.run_patrol_step();
+
+        let turned = patrol_matrix.guards.first()
+            .is_some_and(|guard| guard.position == position && guard.turn_count > turn_count);
+        if turned {
+            turn_steps.insert(state, path.len() - 1);
+        }
+
+        // the guard is back at a turn it has already taken, so everything walked since then is the loop
+        if patrol_matrix.ended_in_loop && cycle.is_empty() {
+            if let Some(start) = turn_steps.get(&state) {
+                cycle = path[*start..].iter().copied().collect();
+            }
+        }
+
+        if !replay_options.turns_only || turned || patrol_matrix.guards.is_empty() {
+            draw(&patrol_matrix, position, &cycle);
+        }
+    }
 
-        // clone the original matrix and add in the new object
-        .map(|((x, y), _p)| {
-            let mut new_patrol_matrix = patrol_matrix.clone();
+    match patrol_matrix.ended_in_loop {
+        true => println!("The guard is stuck in a loop through {} cells after {} steps.", cycle.len(), path.len()),
+        false => println!("The guard left the map after {} steps, seeing {}.", path.len(), patrol_matrix.count_seen()),
+    }
+}
+
+// draw the part of the map inside the viewport, kept around the center but never past the edges
+fn render_frame(
+    patrol_matrix: &PatrolMatrix,
+    center: (isize, isize),
+    viewport: Option<(isize, isize)>,
+    added_obstacle: Option<(isize, isize)>,
+    cycle: &HashSet<(isize, isize)>
+) -> String {
+    let (width, height) = viewport.unwrap_or((patrol_matrix.width, patrol_matrix.height));
+    let (width, height) = (width.min(patrol_matrix.width), height.min(patrol_matrix.height));
+    let left = (center.0 - width / 2).clamp(0, patrol_matrix.width - width);
+    let top = (center.1 - height / 2).clamp(0, patrol_matrix.height - height);
+
+    let mut frame = String::new();
+    for y in top..top + height {
+        for x in left..left + width {
+            let patrol_object = patrol_matrix.get(x, y).unwrap();
+            let c = position_to_char(&patrol_object);
+
+            if added_obstacle == Some((x, y)) {
+                frame.push_str(&format!("{ANSI_RED}O{ANSI_RESET}"));
+            } else if let PatrolObject::Guard(_) = patrol_object {
+                frame.push_str(&format!("{ANSI_BOLD}{c}{ANSI_RESET}"));
+            } else if cycle.contains(&(x, y)) {
+                frame.push_str(&format!("{ANSI_YELLOW}{c}{ANSI_RESET}"));
+            } else {
+                frame.push(c);
+            }
+        }
+        frame.push('\n');
+    }
+
+    frame
+}
+
+// what a cell is drawn as in the exported image, in the same order as IMAGE_LEGEND
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ImageCell {
+    Unvisited,
+    Visited,
+    Obstacle,
+    GuardStart,
+    LoopObstacle,
+}
 
-            new_patrol_matrix.set(x, y, PatrolObject::Obstacle);
+const IMAGE_LEGEND: [(&str, [u8; 3]); 5] = [
+    ("unvisited", [255, 255, 255]),
+    ("visited", [120, 170, 255]),
+    ("obstacle", [40, 40, 40]),
+    ("guard start", [0, 170, 0]),
+    ("loop obstacle", [220, 30, 30]),
+];
 
-            new_patrol_matrix
+// run the patrol and write where the guard went along with every obstacle part two found to an
+// image, so the loop obstacles can be checked by eye
+fn export_image(patrol_matrix: &PatrolMatrix, file: &str, scale: usize) {
+    let loop_obstacles = find_loop_obstacles_by_jumping(patrol_matrix, path_candidates(patrol_matrix));
+    let cells = image_cells(patrol_matrix, &loop_obstacles);
+
+    let mut writer = fs::File::create(file).expect("Should have been able to create the file");
+    write_ppm(&cells, patrol_matrix.width as usize, scale, &mut writer)
+        .expect("Should have been able to write the image");
+
+    println!("Wrote {file} with {} loop obstacles.", loop_obstacles.len());
+    for (name, [r, g, b]) in IMAGE_LEGEND {
+        println!("{name}: rgb({r}, {g}, {b})");
+    }
+}
+
+fn image_cells(patrol_matrix: &PatrolMatrix, loop_obstacles: &[(isize, isize)]) -> Vec<ImageCell> {
+    let starts = patrol_matrix.guards.iter().map(|guard| guard.position).collect::<HashSet<_>>();
+    let loop_obstacles = loop_obstacles.iter().collect::<HashSet<_>>();
+
+    let mut patrolled = patrol_matrix.clone();
+    patrolled.run_patrol();
+
+    patrolled.iter()
+        .map(|(position, patrol_object)| {
+            if loop_obstacles.contains(&position) {
+                ImageCell::LoopObstacle
+            } else if starts.contains(&position) {
+                ImageCell::GuardStart
+            } else {
+                match patrol_object {
+                    PatrolObject::Obstacle => ImageCell::Obstacle,
+                    PatrolObject::Seen | PatrolObject::Guard(_) => ImageCell::Visited,
+                    PatrolObject::Unseen => ImageCell::Unvisited,
+                }
+            }
         })
-        .collect::<Vec<PatrolMatrix>>()
+        .collect()
+}
+
+// write the cells as a binary PPM image with every cell a scale by scale square of pixels, and
+// the legend as comments in the header
+fn write_ppm(cells: &[ImageCell], width: usize, scale: usize, writer: &mut impl Write) -> io::Result<()> {
+    let height = cells.len() / width;
+
+    writeln!(writer, "P6")?;
+    for (name, [r, g, b]) in IMAGE_LEGEND {
+        writeln!(writer, "# {name}: {r} {g} {b}")?;
+    }
+    writeln!(writer, "{} {}\n255", width * scale, height * scale)?;
+
+    let mut row = Vec::with_capacity(width * scale * 3);
+    for cells in cells.chunks(width) {
+        row.clear();
+        for cell in cells {
+            let (_, color) = IMAGE_LEGEND[*cell as usize];
+            for _ in 0..scale {
+                row.extend_from_slice(&color);
+            }
+        }
+
+        for _ in 0..scale {
+            writer.write_all(&row)?;
+        }
+    }
+
+    Ok(())
+}
+
+// every cell an obstacle could be added to
+fn empty_candidates(patrol_matrix: &PatrolMatrix) -> Vec<(isize, isize)> {
+    patrol_matrix.iter()
+        .filter(|(_, p)| *p == PatrolObject::Unseen)
+        .map(|(position, _)| position)
+        .collect()
+}
 
-        // use rayon to go through all new matrix in parallel, running the patrol and counting the
-        // number of loops found
-        .par_iter_mut()
-        .map(|patrol_matrix| {
+// every cell on the guard's patrol except where the guard starts
+fn path_candidates(patrol_matrix: &PatrolMatrix) -> Vec<(isize, isize)> {
+    let mut patrolled = patrol_matrix.clone();
+    patrolled.run_patrol();
 
-            // run the patrol
-            patrol_matrix.run_patrol();
+    empty_candidates(patrol_matrix)
+        .into_iter()
+        .filter(|(x, y)| patrolled.get(*x, *y) == Some(PatrolObject::Seen))
+        .collect()
+}
+
+// the candidate cells where adding an obstacle traps the guard in a loop
+fn find_loop_obstacles(patrol_matrix: &PatrolMatrix, candidates: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    candidates
+        // use rayon to go through every candidate in parallel, starting a new patrol of the shared
+        // map with the new obstacle added and running it
+        .into_par_iter()
+        .filter(|added_obstacle| {
+            let mut new_patrol_matrix = patrol_matrix.with_obstacle(*added_obstacle);
+
+            new_patrol_matrix.run_patrol();
 
-            // return if the patrol ended in a loop
-            // this is calculated by keeping track of turns the guard took in the
-            // PatrolMatrix.patrol_turns vec. If we take the same turn twice we know the guard is
-            // in a loop
-            patrol_matrix.ended_in_loop
+            new_patrol_matrix.ended_in_loop
         })
+        .collect()
+}
 
-        // count the loops
-        .filter(|b| *b)
-        .count();
+// the same as find_loop_obstacles, but every simulation jumps from turn to turn using a jump table
+// built once for the map instead of walking a new patrol one cell at a time, which only works
+// for a single guard
+fn find_loop_obstacles_by_jumping(patrol_matrix: &PatrolMatrix, candidates: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    let [guard] = patrol_matrix.guards.as_slice() else {
+        return find_loop_obstacles(patrol_matrix, candidates)
+    };
+    let start = (guard.position, guard.direction);
+    let jump_table = JumpTable::new(&patrol_matrix.map);
 
-    let elapsed = now.elapsed();
+    candidates
+        .into_par_iter()
+        .filter(|added_obstacle| jump_table.ends_in_loop(start, patrol_matrix.turn_rule, *added_obstacle))
+        .collect()
+}
 
-    println!("There are {loop_count} loops.\nFound in {:.2?}.", elapsed);
+// for every cell and direction, the cell a guard walking that way stops on before turning, or None
+// if the guard walks off the map
+struct JumpTable {
+    stops: Vec<[Option<(isize, isize)>; 4]>,
+    width: isize,
+}
 
-    loop_count as isize
+impl JumpTable {
+    fn new(map: &PatrolMap) -> JumpTable {
+        let width = map.width;
+        let mut stops = vec![[None; 4]; (width * map.height) as usize];
+
+        let positions = (0..map.height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .collect::<Vec<(isize, isize)>>();
+
+        for direction in [Direction::Up, Direction::Right, Direction::Down, Direction::Left] {
+            // the cell in front has to be filled in first, which for up and left is the earlier
+            // cell in row-major order and for right and down the later one
+            let ordered = match direction {
+                Direction::Up | Direction::Left => positions.clone(),
+                Direction::Right | Direction::Down => positions.iter().rev().copied().collect(),
+            };
+
+            for position in ordered {
+                let forward = position_forward(position, &direction);
+                let stop = if !map.is_in_bounds(forward) {
+                    None
+                } else if map.is_obstacle(forward) {
+                    Some(position)
+                } else {
+                    stops[map.index(forward)][direction as usize]
+                };
+
+                stops[map.index(position)][direction as usize] = stop;
+            }
+        }
+
+        JumpTable { stops, width }
+    }
+
+    fn index(&self, position: (isize, isize)) -> usize {
+        (position.0 + position.1 * self.width) as usize
+    }
+
+    // where the guard stops, taking into account the one obstacle added on top of the map
+    fn next_stop(&self, position: (isize, isize), direction: Direction, added_obstacle: (isize, isize)) -> Option<(isize, isize)> {
+        let stop = self.stops[self.index(position)][direction as usize];
+
+        // the added obstacle only matters if it is between the guard and where it would have stopped
+        let ((x, y), (ox, oy)) = (position, added_obstacle);
+        let is_blocking = match direction {
+            Direction::Up    => ox == x && oy < y && stop.is_none_or(|(_, sy)| oy >= sy),
+            Direction::Down  => ox == x && oy > y && stop.is_none_or(|(_, sy)| oy <= sy),
+            Direction::Left  => oy == y && ox < x && stop.is_none_or(|(sx, _)| ox >= sx),
+            Direction::Right => oy == y && ox > x && stop.is_none_or(|(sx, _)| ox <= sx),
+        };
+
+        if is_blocking {
+            Some(position_forward(added_obstacle, &direction_reversed(&direction)))
+        } else {
+            stop
+        }
+    }
+
+    fn ends_in_loop(&self, start: ((isize, isize), Direction), turn_rule: TurnRule, added_obstacle: (isize, isize)) -> bool {
+        let mut turn_states = vec![0u8; self.stops.len()];
+        let (mut position, mut direction) = start;
+        let mut turn_count = 0;
+
+        while let Some(stop) = self.next_stop(position, direction, added_obstacle) {
+            let (index, bit) = (self.index(stop), turn_state_bit(direction, turn_rule.parity(turn_count)));
+            if turn_states[index] & bit != 0 {
+                return true
+            }
+            turn_states[index] |= bit;
+
+            position = stop;
+            direction = turn_rule.turn(&direction, turn_count);
+            turn_count += 1;
+        }
+
+        false
+    }
 }
 
 type PatrolPosition = ((isize, isize), PatrolObject);
 
+// a guard's position and direction, along with which way it turns next for the alternating rule
+type GuardState = ((isize, isize), Direction, usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum LoopDetection {
+    // scan every turn taken so far, kept to compare against in the benchmark
+    TurnHistory,
+    // look up the position and direction in a bitset
+    StateBitset,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TurnRule {
+    Right,
+    Left,
+    Reverse,
+    // right, then left, then right again and so on
+    Alternating,
+}
+
+impl TurnRule {
+    fn turn(&self, direction: &Direction, turn_count: usize) -> Direction {
+        match self {
+            TurnRule::Right => direction_to_right(direction),
+            TurnRule::Left => direction_to_left(direction),
+            TurnRule::Reverse => direction_reversed(direction),
+            TurnRule::Alternating if self.parity(turn_count) == 0 => direction_to_right(direction),
+            TurnRule::Alternating => direction_to_left(direction),
+        }
+    }
+
+    // only the alternating rule turns differently depending on how many turns came before
+    fn parity(&self, turn_count: usize) -> usize {
+        match self {
+            TurnRule::Alternating => turn_count % 2,
+            _ => 0,
+        }
+    }
+}
+
+fn turn_rule_by_name(name: &str) -> Option<TurnRule> {
+    match name {
+        "right" => Some(TurnRule::Right),
+        "left" => Some(TurnRule::Left),
+        "reverse" => Some(TurnRule::Reverse),
+        "alternating" => Some(TurnRule::Alternating),
+        _ => None,
+    }
+}
+
+// what happens when a guard walks into a cell another guard is on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Collision {
+    // the guards walk through each other
+    Pass,
+    // the guard turns as if the other guard were an obstacle
+    Block,
+    // both guards stop where they meet and the patrol ends for them
+    Stop,
+}
+
+fn collision_by_name(name: &str) -> Option<Collision> {
+    match name {
+        "pass" => Some(Collision::Pass),
+        "block" => Some(Collision::Block),
+        "stop" => Some(Collision::Stop),
+        _ => None,
+    }
+}
+
+// which bit in a cell's turn states is set when a guard turns there
+fn turn_state_bit(direction: Direction, parity: usize) -> u8 {
+    1 << (direction as u8 + 4 * parity as u8)
+}
+
+#[derive(Clone, Copy)]
+struct Guard {
+    // which of the map's starting guards this is
+    id: usize,
+    position: (isize, isize),
+    direction: Direction,
+    turn_count: usize,
+    // whether the guard stepped onto its cell and hasn't turned there since
+    straight: bool,
+}
+
+impl Guard {
+    fn state(&self, turn_rule: TurnRule) -> GuardState {
+        (self.position, self.direction, turn_rule.parity(self.turn_count))
+    }
+}
+
+// the turns a single guard has taken, kept to tell when it starts repeating itself
+#[derive(Clone)]
+struct TurnLog {
+    history: Vec<GuardState>,
+    // a bit per direction and turn parity for every cell, set when the guard turns there
+    states: Vec<u8>,
+}
+
+impl TurnLog {
+    fn new(cell_count: usize) -> TurnLog {
+        TurnLog {
+            history: Vec::new(),
+            states: vec![0; cell_count],
+        }
+    }
+
+    fn is_stuck_in_loop(&self, state: GuardState, loop_detection: LoopDetection, width: isize) -> bool {
+        match loop_detection {
+            LoopDetection::TurnHistory => self.history.contains(&state),
+            LoopDetection::StateBitset => {
+                let ((x, y), direction, parity) = state;
+                self.states[(x + y * width) as usize] & turn_state_bit(direction, parity) != 0
+            },
+        }
+    }
+
+    fn record_turn(&mut self, state: GuardState, loop_detection: LoopDetection, width: isize) {
+        match loop_detection {
+            LoopDetection::TurnHistory => self.history.push(state),
+            LoopDetection::StateBitset => {
+                let ((x, y), direction, parity) = state;
+                self.states[(x + y * width) as usize] |= turn_state_bit(direction, parity);
+            },
+        }
+    }
+}
+
+// the parts of the puzzle that never change during a patrol, shared by every simulation of it
+struct PatrolMap {
+    obstacles: Vec<bool>,
+    // cells the input already marks as visited
+    seen: Vec<bool>,
+    // where every guard starts and which way it faces, in the order they appear on the map
+    starts: Vec<((isize, isize), Direction)>,
+    width: isize,
+    height: isize,
+}
+
+impl PatrolMap {
+    fn new(input: &str) -> Result<PatrolMap, GridError> {
+        let grid = grid::parse_rows(input)?;
+        let patrol_objects = process_input(&grid.rows)?;
+
+        let starts = patrol_objects.iter()
+            .enumerate()
+            .filter_map(|(index, patrol_object)| match patrol_object {
+                PatrolObject::Guard(direction) => {
+                    Some(((index as isize % grid.width, index as isize / grid.width), *direction))
+                },
+                _ => None,
+            })
+            .collect();
+
+        Ok(PatrolMap {
+            obstacles: patrol_objects.iter().map(|p| *p == PatrolObject::Obstacle).collect(),
+            seen: patrol_objects.iter().map(|p| *p == PatrolObject::Seen).collect(),
+            starts,
+            width: grid.width,
+            height: grid.height,
+        })
+    }
+
+    fn index(&self, position: (isize, isize)) -> usize {
+        (position.0 + position.1 * self.width) as usize
+    }
+
+    fn is_in_bounds(&self, position: (isize, isize)) -> bool {
+        let (x, y) = position;
+        !(x < 0 || x >= self.width || y < 0 || y >= self.height)
+    }
+
+    fn is_obstacle(&self, position: (isize, isize)) -> bool {
+        self.is_in_bounds(position) && self.obstacles[self.index(position)]
+    }
+}
+
+struct PatrolStats {
+    steps: usize,
+    turns: usize,
+    distinct_cells: usize,
+    revisits: usize,
+    // cells guards walked straight through both up or down and left or right. The corners where
+    // they turned are only walked through on one axis at a time, so they don't count
+    crossings: usize,
+}
+
+impl Display for PatrolStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Steps: {}", self.steps)?;
+        writeln!(f, "Turns: {}", self.turns)?;
+        writeln!(f, "Distinct cells: {}", self.distinct_cells)?;
+        writeln!(f, "Revisits: {}", self.revisits)?;
+        write!(f, "Crossings: {}", self.crossings)
+    }
+}
+
+// the bit in a visited cell for a visit the input recorded without a direction
+const SEEN_IN_INPUT: u8 = 1 << 4;
+
+// a single patrol of a shared map, with at most one obstacle added on top of it
 #[derive(Clone)]
 struct PatrolMatrix {
-    pub patrol_objects: Vec<PatrolObject>,
-    pub patrol_turns: Vec<PatrolPosition>,
-    pub cached_guard: Option<PatrolPosition>,
+    pub map: Arc<PatrolMap>,
+    pub added_obstacle: Option<(isize, isize)>,
+    // the guards still on patrol, in the order they take their steps
+    pub guards: Vec<Guard>,
+    // a bit for every direction a guard has faced on each cell
+    pub visited: Vec<u8>,
+    // a bit for every direction a guard walked straight through each cell in, without turning there
+    pub passed: Vec<u8>,
+    // how many times guards have stepped forward, turned and stepped onto a cell they had already
+    // been on
+    pub steps: usize,
+    pub turns: usize,
+    pub revisits: usize,
+    // the turns of every guard, by guard id
+    pub turn_logs: Vec<TurnLog>,
+    pub turn_rule: TurnRule,
+    pub collision: Collision,
+    pub loop_detection: LoopDetection,
+    // with guards that can get in each other's way a loop only repeats once all of them are back
+    // where they were, so the state of every guard is kept whenever one of them turns
+    pub patrol_states: HashSet<Vec<GuardState>>,
+    // where guards walked into each other under the stop collision rule
+    pub collisions: Vec<(isize, isize)>,
     pub width: isize,
     pub height: isize,
     pub ended_in_loop: bool,
 }
 
 impl PatrolMatrix {
-    fn new(input: &str) -> PatrolMatrix {
+    fn new(input: &str) -> Result<PatrolMatrix, GridError> {
+        Ok(PatrolMatrix::from_map(Arc::new(PatrolMap::new(input)?)))
+    }
+
+    fn from_map(map: Arc<PatrolMap>) -> PatrolMatrix {
+        let cell_count = map.obstacles.len();
+        let guards = map.starts.iter()
+            .enumerate()
+            .map(|(id, (position, direction))| Guard { id, position: *position, direction: *direction, turn_count: 0, straight: false })
+            .collect::<Vec<Guard>>();
+
+        // there is no telling which way a guard faced on a cell the input marks as visited
+        let mut visited = map.seen.iter()
+            .map(|seen| if *seen { SEEN_IN_INPUT } else { 0 })
+            .collect::<Vec<u8>>();
+        for guard in &guards {
+            visited[map.index(guard.position)] |= 1 << guard.direction as u8;
+        }
+
         PatrolMatrix {
-            patrol_objects: process_input(input),
-            patrol_turns: Vec::new(),
-            width: input.find('\n').unwrap() as isize,
-            height: (input.chars().filter(|c| *c == '\n').count() + 1) as isize,
+            added_obstacle: None,
+            turn_logs: vec![TurnLog::new(cell_count); guards.len()],
+            guards,
+            visited,
+            passed: vec![0; cell_count],
+            steps: 0,
+            turns: 0,
+            revisits: 0,
+            turn_rule: TurnRule::Right,
+            collision: Collision::Pass,
+            loop_detection: LoopDetection::StateBitset,
+            patrol_states: HashSet::new(),
+            collisions: Vec::new(),
+            width: map.width,
+            height: map.height,
             ended_in_loop: false,
-            cached_guard: None,
+            map,
+        }
+    }
+
+    // a patrol of the same map under the same rules from the start, with an obstacle added
+    fn with_obstacle(&self, position: (isize, isize)) -> PatrolMatrix {
+        PatrolMatrix {
+            added_obstacle: Some(position),
+            turn_rule: self.turn_rule,
+            collision: self.collision,
+            loop_detection: self.loop_detection,
+            ..PatrolMatrix::from_map(Arc::clone(&self.map))
         }
     }
 
-    fn get(&self, x: isize, y: isize) -> Option<&PatrolObject> {
+    // what is at a position, put together from the map, the guards and where they have been
+    fn get(&self, x: isize, y: isize) -> Option<PatrolObject> {
         if !self.is_in_bounds((x, y)) {
             return None
         }
 
-        let index = x + y * self.width;
-        self.patrol_objects.get(index as usize)
-    }
+        if self.is_obstacle((x, y)) {
+            return Some(PatrolObject::Obstacle)
+        }
 
-    fn set(&mut self, x: isize, y: isize, object: PatrolObject) {
-        if !self.is_in_bounds((x, y)) {
-            return
+        if let Some(guard) = self.guards.iter().find(|guard| guard.position == (x, y)) {
+            return Some(PatrolObject::Guard(guard.direction))
         }
 
-        let index = x + y * self.width;
-        self.patrol_objects[index as usize] = object
+        match self.visited[self.map.index((x, y))] {
+            0 => Some(PatrolObject::Unseen),
+            _ => Some(PatrolObject::Seen),
+        }
+    }
+
+    fn is_obstacle(&self, position: (isize, isize)) -> bool {
+        self.added_obstacle == Some(position) || self.map.is_obstacle(position)
     }
 
     fn iter(&self) -> PatrolMatrixIterator {
@@ -124,88 +855,142 @@ impl PatrolMatrix {
     }
 
     fn is_in_bounds(&self, position: (isize, isize)) -> bool {
-        let (x, y) = position;
-        !(x < 0 || x >= self.width || y < 0 || y >= self.height)
+        self.map.is_in_bounds(position)
     }
 
     fn run_patrol(&mut self) {
-        while self.find_guard().is_some() {
+        while !self.guards.is_empty() {
             self.run_patrol_step();
         }
     }
 
+    // move every guard on patrol one step forward or have it turn
     fn run_patrol_step(&mut self) {
-        let guard = self.find_guard();
-        if guard.is_none() {
-            return;
-        }
+        let shared_loop = self.collision != Collision::Pass && self.guards.len() > 1;
+        let mut finished = vec![false; self.guards.len()];
+        let mut any_turned = false;
+
+        for index in 0..self.guards.len() {
+            if finished[index] {
+                continue
+            }
+
+            let guard = self.guards[index];
+            let forward_position = position_forward(guard.position, &guard.direction);
+            let guard_forward = self.guards.iter()
+                .enumerate()
+                .position(|(other, guard)| other != index && !finished[other] && guard.position == forward_position);
 
-        let (guard_position, guard_object) = guard.unwrap();
+            let is_blocked = self.is_obstacle(forward_position)
+                || (self.collision == Collision::Block && guard_forward.is_some());
 
-        let PatrolObject::Guard(ref direction) = guard_object else { todo!() };
+            if is_blocked {
+                any_turned = true;
 
-        let forward_position = position_forward(guard_position, &direction);
-        let object_forward = self.get(forward_position.0, forward_position.1);
+                // a lone guard is in a loop as soon as it is back at a turn it already took
+                if !shared_loop {
+                    let state = guard.state(self.turn_rule);
+                    let turn_log = &mut self.turn_logs[guard.id];
+                    if turn_log.is_stuck_in_loop(state, self.loop_detection, self.width) {
+                        finished[index] = true;
+                        self.ended_in_loop = true;
+                        continue
+                    }
 
-        match object_forward {
-            // if there is an Obstacle in front of the guard turn right
-            Some(PatrolObject::Obstacle) => {
-                // first check if we are in an infinite loop and if we are then return early
-                if self.is_stuck_in_loop((guard_position, guard_object.clone())) {
-                    self.set(guard_position.0, guard_position.1, PatrolObject::Seen);
-                    // clear the cache so we end the search
-                    self.cached_guard = None;
-                    self.ended_in_loop = true;
-                    return
+                    // first store the turn so that if we get here again we know we are stuck in a loop
+                    turn_log.record_turn(state, self.loop_detection, self.width);
                 }
 
-                // first store the turn so that if we get here again we know we are stuck in a loop
-                self.patrol_turns.push((guard_position, guard_object.clone()));
+                let guard = &mut self.guards[index];
+                guard.direction = self.turn_rule.turn(&guard.direction, guard.turn_count);
+                guard.turn_count += 1;
+                guard.straight = false;
+                self.turns += 1;
+                self.visited[self.map.index(guard.position)] |= 1 << guard.direction as u8;
+                continue
+            }
 
-                self.set_guard(((guard_position.0, guard_position.1), PatrolObject::Guard(direction_to_right(&direction))));
-            },
-            // otherwise move forward and set the previous position to seen
-            _ => {
-                let (x, y) = position_forward(guard_position, &direction);
-                self.set_guard(((x, y), PatrolObject::Guard(direction.clone())));
+            // otherwise move forward and mark the new position as visited
+            if guard.straight {
+                self.passed[self.map.index(guard.position)] |= 1 << guard.direction as u8;
+            }
+            if !self.is_in_bounds(forward_position) {
+                finished[index] = true;
+                continue
+            }
+            self.guards[index].position = forward_position;
+            self.guards[index].straight = true;
+            self.steps += 1;
 
-                // set the previous space to seen
-                self.set(guard_position.0, guard_position.1, PatrolObject::Seen);
+            let visited = &mut self.visited[self.map.index(forward_position)];
+            if *visited != 0 {
+                self.revisits += 1;
             }
-        };
-    }
+            *visited |= 1 << guard.direction as u8;
 
-    fn set_guard(&mut self, patrol_position: PatrolPosition) {
-        if !self.is_in_bounds((patrol_position.0.0, patrol_position.0.1)) {
-            self.cached_guard = None;
-            return
+            if let (Some(other), Collision::Stop) = (guard_forward, self.collision) {
+                self.collisions.push(forward_position);
+                finished[index] = true;
+                finished[other] = true;
+            }
         }
-        self.cached_guard = Some(patrol_position.clone());
-        self.set(patrol_position.0.0, patrol_position.0.1, patrol_position.1)
-    }
 
-    fn find_guard(&self) -> Option<PatrolPosition>{
-        match self.cached_guard {
-            Some(_) => self.cached_guard,
-            None => {
-                self.iter().find(|(_, p)| {
-                    match p {
-                        PatrolObject::Guard(_) => true,
-                        _ => false,
-                    }
-                })
+        let mut finished = finished.into_iter();
+        self.guards.retain(|_| !finished.next().unwrap());
+
+        if shared_loop && any_turned {
+            let states = self.guards.iter().map(|guard| guard.state(self.turn_rule)).collect();
+            if !self.patrol_states.insert(states) {
+                self.guards.clear();
+                self.ended_in_loop = true;
             }
         }
     }
 
     fn count_seen(&self) -> usize {
-        self.iter()
-            .filter(|(_, p)| {
-                match p {
-                    PatrolObject::Seen => true,
-                    _ => false
-                }
-            }).count()
+        self.visited.iter().filter(|visited| **visited != 0).count()
+    }
+
+    fn stats(&self) -> PatrolStats {
+        let vertical = 1 << Direction::Up as u8 | 1 << Direction::Down as u8;
+        let horizontal = 1 << Direction::Left as u8 | 1 << Direction::Right as u8;
+
+        PatrolStats {
+            steps: self.steps,
+            turns: self.turns,
+            distinct_cells: self.count_seen(),
+            revisits: self.revisits,
+            crossings: self.passed.iter()
+                .filter(|passed| *passed & vertical != 0 && *passed & horizontal != 0)
+                .count(),
+        }
+    }
+
+    // draw the patrol the way the puzzle does, with | and - where guards walked up and down or
+    // left and right, + where they did both and O for the added obstacle
+    fn render_path(&self) -> String {
+        let vertical = 1 << Direction::Up as u8 | 1 << Direction::Down as u8;
+        let horizontal = 1 << Direction::Left as u8 | 1 << Direction::Right as u8;
+
+        let mut rendered = String::new();
+        for ((x, y), patrol_object) in self.iter() {
+            let visited = self.visited[self.map.index((x, y))];
+            let c = match patrol_object {
+                _ if self.added_obstacle == Some((x, y)) => 'O',
+                PatrolObject::Seen if visited & (vertical | horizontal) == 0 => 'X',
+                PatrolObject::Seen if visited & vertical == 0 => '-',
+                PatrolObject::Seen if visited & horizontal == 0 => '|',
+                PatrolObject::Seen => '+',
+                patrol_object => position_to_char(&patrol_object),
+            };
+            rendered.push(c);
+
+            if x == self.width - 1 {
+                rendered.push('\n');
+            }
+        }
+
+        rendered
     }
 
     fn object_position_list(&self) -> VecDeque<PatrolPosition> {
@@ -215,23 +1000,16 @@ impl PatrolMatrix {
             for x in 0..self.width {
                 let c = self.get(x, y).unwrap();
 
-                list.push_back(((x, y), c.clone()));
+                list.push_back(((x, y), c));
             }
         }
 
         list
     }
-
-    fn is_stuck_in_loop(&self, patrol_turn: PatrolPosition) -> bool {
-        let ((x1, y1), p1) = patrol_turn;
-
-        self.patrol_turns.iter().any(|e| {
-            let ((x2, y2), p2) = e;
-            x1 == *x2 && y1 == *y2 && p1 == *p2
-        })
-    }
 }
 
+// renders the map the way the input is written, so that process_input reads a rendered map back
+// into an equal PatrolMatrix
 impl Display for PatrolMatrix {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 
@@ -239,7 +1017,7 @@ impl Display for PatrolMatrix {
         for ((x, _y), patrol_object) in self.iter() {
             display.push(position_to_char(&patrol_object));
 
-            if x % self.width == self.width - 1 {
+            if x == self.width - 1 {
                 display.push('\n');
             }
         }
@@ -255,7 +1033,20 @@ fn position_to_char(patrol_object: &PatrolObject) -> char {
         PatrolObject::Guard(Direction::Up) => '^',
         PatrolObject::Guard(Direction::Right) => '>',
         PatrolObject::Guard(Direction::Left) => '<',
-        PatrolObject::Guard(Direction::Down) => '⌄',
+        PatrolObject::Guard(Direction::Down) => 'v',
+    }
+}
+
+fn char_to_position(c: char) -> Option<PatrolObject> {
+    match c {
+        '.' => Some(PatrolObject::Unseen),
+        'X' => Some(PatrolObject::Seen),
+        '#' => Some(PatrolObject::Obstacle),
+        '^' => Some(PatrolObject::Guard(Direction::Up)),
+        '>' => Some(PatrolObject::Guard(Direction::Right)),
+        '<' => Some(PatrolObject::Guard(Direction::Left)),
+        'v' => Some(PatrolObject::Guard(Direction::Down)),
+        _ => None,
     }
 }
 
@@ -277,7 +1068,26 @@ fn direction_to_right(direction: &Direction) -> Direction {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Copy)]
+fn direction_to_left(direction: &Direction) -> Direction {
+    match direction {
+        Direction::Up    => Direction::Left,
+        Direction::Down  => Direction::Right,
+        Direction::Right => Direction::Up,
+        Direction::Left  => Direction::Down,
+    }
+}
+
+fn direction_reversed(direction: &Direction) -> Direction {
+    match direction {
+        Direction::Up    => Direction::Down,
+        Direction::Down  => Direction::Up,
+        Direction::Right => Direction::Left,
+        Direction::Left  => Direction::Right,
+    }
+}
+
+// what a cell holds, read from the input and put back together when showing a patrol
+#[derive(Clone, PartialEq, Eq, Copy, Debug)]
 enum PatrolObject {
     Guard(Direction),
     Obstacle,
@@ -285,7 +1095,7 @@ enum PatrolObject {
     Seen
 }
 
-#[derive(Clone, PartialEq, Eq, Copy)]
+#[derive(Clone, PartialEq, Eq, Copy, Hash, Debug)]
 enum Direction {
     Up,
     Right,
@@ -293,19 +1103,17 @@ enum Direction {
     Left
 }
 
-fn process_input(input: &str) -> Vec<PatrolObject> {
-    input
-        // filter out whitespace and collect into Vec<char>
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .map(|c| {
-            match c {
-                '^' => PatrolObject::Guard(Direction::Up),
-                '#' => PatrolObject::Obstacle,
-                _ => PatrolObject::Unseen,
-            }
+fn process_input(rows: &[&str]) -> Result<Vec<PatrolObject>, GridError> {
+    rows
+        // join the rows together and collect into Vec<PatrolObject>
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .map(move |(column, cell)| char_to_position(cell).ok_or(GridError::InvalidCell { row, column, cell }))
         })
-        .collect::<Vec<PatrolObject>>()
+        .collect::<Result<Vec<PatrolObject>, GridError>>()
 }
 
 struct PatrolMatrixIterator {
@@ -331,6 +1139,343 @@ impl<'a> Iterator for PatrolMatrixIterator {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    // the parts of a patrol a rendered map shows, see map_state
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct MapState {
+        width: isize,
+        height: isize,
+        obstacles: Vec<bool>,
+        // in reading order, the way a map lists them
+        guards: Vec<((isize, isize), Direction)>,
+        visited: Vec<bool>,
+    }
+
+    // what a rendered map records: the obstacles, counting an added one, where the guards are and
+    // which way they face, and which cells have been visited. Parsing a rendered map gives back the
+    // same map state, as long as no two guards share a cell, since only one of them can be drawn.
+    fn map_state(patrol_matrix: &PatrolMatrix) -> MapState {
+        let mut guards = patrol_matrix.guards.iter()
+            .map(|guard| (guard.position, guard.direction))
+            .collect::<Vec<((isize, isize), Direction)>>();
+        guards.sort_by_key(|((x, y), _)| (*y, *x));
+
+        MapState {
+            width: patrol_matrix.width,
+            height: patrol_matrix.height,
+            obstacles: (0..patrol_matrix.height)
+                .flat_map(|y| (0..patrol_matrix.width).map(move |x| (x, y)))
+                .map(|position| patrol_matrix.is_obstacle(position))
+                .collect(),
+            guards,
+            visited: patrol_matrix.visited.iter().map(|visited| *visited != 0).collect(),
+        }
+    }
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn loop_detection_test() {
+        let patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap();
+
+        let expected = vec![(3, 6), (6, 7), (7, 7), (1, 8), (3, 8), (7, 9)];
+        for loop_detection in [LoopDetection::TurnHistory, LoopDetection::StateBitset] {
+            let patrol_matrix = PatrolMatrix {
+                loop_detection,
+                ..patrol_matrix.clone()
+            };
+
+            let mut loop_obstacles = find_loop_obstacles(&patrol_matrix, empty_candidates(&patrol_matrix));
+            loop_obstacles.sort_by_key(|(x, y)| (*y, *x));
+            assert_eq!(loop_obstacles, expected);
+
+            let mut loop_obstacles = find_loop_obstacles(&patrol_matrix, path_candidates(&patrol_matrix));
+            loop_obstacles.sort_by_key(|(x, y)| (*y, *x));
+            assert_eq!(loop_obstacles, expected);
+        }
+
+        let mut loop_obstacles = find_loop_obstacles_by_jumping(&patrol_matrix, empty_candidates(&patrol_matrix));
+        loop_obstacles.sort_by_key(|(x, y)| (*y, *x));
+        assert_eq!(loop_obstacles, expected);
+    }
+
+    // a guard walking up from (1, 2) turns right at (1, 1), then down at (2, 1), left at (2, 2)
+    // and back up at (1, 2), going round the same four cells forever
+    const LOOP_EXAMPLE: &str = ".#..
+...#
+#^..
+..#.";
+
+    fn patrol_with(input: &str, turn_rule: TurnRule, collision: Collision) -> PatrolMatrix {
+        let mut patrol_matrix = PatrolMatrix::new(input).unwrap();
+        patrol_matrix.turn_rule = turn_rule;
+        patrol_matrix.collision = collision;
+        patrol_matrix.run_patrol();
+        patrol_matrix
+    }
+
+    #[test]
+    fn turn_rule_test() {
+        let patrolled = patrol_with(LOOP_EXAMPLE, TurnRule::Right, Collision::Pass);
+        assert!(patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 4);
+
+        // right at (1, 1), then left at (2, 1) and up off the map
+        let patrolled = patrol_with(LOOP_EXAMPLE, TurnRule::Alternating, Collision::Pass);
+        assert!(!patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 4);
+
+        // left at (1, 1) and off the map
+        let patrolled = patrol_with(LOOP_EXAMPLE, TurnRule::Left, Collision::Pass);
+        assert!(!patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 3);
+
+        // back down past where the guard started
+        let patrolled = patrol_with(LOOP_EXAMPLE, TurnRule::Reverse, Collision::Pass);
+        assert!(!patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 3);
+    }
+
+    #[test]
+    fn multiple_guards_test() {
+        let patrol_matrix = PatrolMatrix::new(">.v.\n..<^").unwrap();
+        let guards = patrol_matrix.guards.iter()
+            .map(|guard| (guard.position, guard.direction as u8))
+            .collect::<Vec<_>>();
+        assert_eq!(guards, vec![((0, 0), 1), ((2, 0), 2), ((2, 1), 3), ((3, 1), 0)]);
+
+        // one guard walks off the map while the other goes round the loop
+        let patrolled = patrol_with("v#..\n...#\n#^..\n..#.", TurnRule::Right, Collision::Pass);
+        assert!(patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 6);
+    }
+
+    #[test]
+    fn collision_test() {
+        // the guards walk through each other and off opposite sides of the map
+        let patrolled = patrol_with(">..<", TurnRule::Right, Collision::Pass);
+        assert_eq!(patrolled.count_seen(), 4);
+        assert!(patrolled.collisions.is_empty());
+
+        // the guards turn in front of each other and walk off the top and bottom
+        let patrolled = patrol_with("....\n>..<\n....", TurnRule::Right, Collision::Block);
+        assert_eq!(patrolled.count_seen(), 6);
+
+        // the first guard walks into the second one, which has already taken a step towards it
+        let patrolled = patrol_with(">..<", TurnRule::Right, Collision::Stop);
+        assert_eq!(patrolled.collisions, vec![(2, 0)]);
+        assert_eq!(patrolled.count_seen(), 4);
+
+        // two guards going round the same loop half a lap apart are only caught once both are back
+        // where they were
+        let patrolled = patrol_with(".#..\n..v#\n#^..\n..#.", TurnRule::Right, Collision::Block);
+        assert!(patrolled.ended_in_loop);
+        assert_eq!(patrolled.count_seen(), 4);
+        assert_eq!(patrolled.patrol_states.len(), 4);
+    }
+
+    #[test]
+    fn jump_table_turn_rule_test() {
+        let patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap();
+
+        for turn_rule in [TurnRule::Right, TurnRule::Left, TurnRule::Reverse, TurnRule::Alternating] {
+            let patrol_matrix = PatrolMatrix {
+                turn_rule,
+                ..patrol_matrix.clone()
+            };
+
+            let mut stepped = find_loop_obstacles(&patrol_matrix, empty_candidates(&patrol_matrix));
+            let mut jumped = find_loop_obstacles_by_jumping(&patrol_matrix, empty_candidates(&patrol_matrix));
+            stepped.sort();
+            jumped.sort();
+            assert_eq!(stepped, jumped);
+        }
+    }
+
+    #[test]
+    fn path_test() {
+        let mut patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap().with_obstacle((3, 6));
+        patrol_matrix.run_patrol();
+
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O+---+.
+........#.
+#.........
+......#...
+";
+        assert_eq!(patrol_matrix.render_path(), expected);
+
+        // up, right, down, left and up again, only seeing the turn at the top a second time
+        let stats = patrol_matrix.stats();
+        assert_eq!(stats.steps, 23);
+        assert_eq!(stats.turns, 4);
+        assert_eq!(stats.distinct_cells, 18);
+        assert_eq!(stats.revisits, 6);
+        // the + cells are all corners the guard turned at, not crossings
+        assert_eq!(stats.crossings, 0);
+    }
+
+    #[test]
+    fn crossings_test() {
+        // turning right at the obstacle and walking off the map faces the cell both ways, but only
+        // walks through it on one axis
+        let mut patrol_matrix = PatrolMatrix::new("#\n.\n^").unwrap();
+        patrol_matrix.run_patrol();
+        assert_eq!(patrol_matrix.stats().turns, 1);
+        assert_eq!(patrol_matrix.stats().crossings, 0);
+
+        // up, right, down and then left back across the way up
+        let mut patrol_matrix = PatrolMatrix::new(".#...\n....#\n.....\n.....\n.^.#.").unwrap();
+        patrol_matrix.run_patrol();
+        assert_eq!(patrol_matrix.render_path(), ".#...\n.+-+#\n.|.|.\n-+-+.\n.|.#.\n");
+        assert_eq!(patrol_matrix.stats().turns, 3);
+        assert_eq!(patrol_matrix.stats().crossings, 1);
+    }
+
+    #[test]
+    fn glyph_test() {
+        let patrol_objects = [
+            PatrolObject::Unseen,
+            PatrolObject::Seen,
+            PatrolObject::Obstacle,
+            PatrolObject::Guard(Direction::Up),
+            PatrolObject::Guard(Direction::Right),
+            PatrolObject::Guard(Direction::Down),
+            PatrolObject::Guard(Direction::Left),
+        ];
+
+        let glyphs = patrol_objects.iter().map(position_to_char).collect::<String>();
+        assert_eq!(glyphs, ".X#^>v<");
+
+        for patrol_object in patrol_objects {
+            assert_eq!(char_to_position(position_to_char(&patrol_object)), Some(patrol_object));
+        }
+    }
+
+    #[test]
+    fn invalid_cell_test() {
+        assert_eq!(
+            PatrolMatrix::new("..#\n.^O").err(),
+            Some(GridError::InvalidCell { row: 1, column: 2, cell: 'O' }),
+        );
+        assert_eq!(char_to_position('?'), None);
+    }
+
+    #[test]
+    fn display_test() {
+        let patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap();
+        assert_eq!(patrol_matrix.to_string(), format!("{EXAMPLE}\n"));
+
+        let mut patrol_matrix = PatrolMatrix::new("....\n.#..\n....").unwrap();
+        patrol_matrix.guards.clear();
+        assert_eq!(patrol_matrix.to_string(), "....\n.#..\n....\n");
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let round_trip = |patrol_matrix: &PatrolMatrix| map_state(&PatrolMatrix::new(&patrol_matrix.to_string()).unwrap());
+
+        for guard in ['^', '>', 'v', '<'] {
+            let input = format!("#....\n..{guard}..\n...#.\nX....");
+            let patrol_matrix = PatrolMatrix::new(&input).unwrap();
+            assert_eq!(patrol_matrix.to_string(), format!("{input}\n"));
+            assert_eq!(round_trip(&patrol_matrix), map_state(&patrol_matrix));
+
+            // and part way through the patrol, with visited cells and the guard facing elsewhere
+            let mut patrol_matrix = patrol_matrix;
+            for _ in 0..3 {
+                patrol_matrix.run_patrol_step();
+                assert_eq!(round_trip(&patrol_matrix), map_state(&patrol_matrix));
+            }
+        }
+
+        // several guards and visited cells, with an obstacle added on top of the map
+        let mut patrol_matrix = PatrolMatrix::new("#..X.\n.^.X>\nX..#.\n.v..X\n").unwrap().with_obstacle((2, 3));
+        assert_eq!(patrol_matrix.to_string(), "#..X.\n.^.X>\nX..#.\n.v#.X\n");
+        assert_eq!(round_trip(&patrol_matrix).guards.len(), 3);
+        assert_eq!(round_trip(&patrol_matrix), map_state(&patrol_matrix));
+
+        while !patrol_matrix.guards.is_empty() {
+            patrol_matrix.run_patrol_step();
+            assert_eq!(round_trip(&patrol_matrix), map_state(&patrol_matrix));
+        }
+
+        let mut patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap();
+        patrol_matrix.run_patrol();
+        let rendered = patrol_matrix.to_string();
+        assert_eq!(rendered.matches('X').count(), 41);
+        assert_eq!(round_trip(&patrol_matrix), map_state(&patrol_matrix));
+    }
+
+    #[test]
+    fn render_frame_test() {
+        let patrol_matrix = PatrolMatrix::new(EXAMPLE).unwrap();
+        let patrol_matrix = patrol_matrix.with_obstacle((3, 6));
+
+        // the viewport is centered on the guard
+        let frame = render_frame(&patrol_matrix, (4, 6), Some((4, 3)), Some((3, 6)), &HashSet::new());
+        assert_eq!(frame, format!("....\n.{ANSI_RED}O{ANSI_RESET}{ANSI_BOLD}^{ANSI_RESET}.\n....\n"));
+
+        // but stops at the edges of the map
+        let cycle = HashSet::from([(0, 0), (1, 0)]);
+        let frame = render_frame(&patrol_matrix, (0, 0), Some((3, 2)), None, &cycle);
+        assert_eq!(frame, format!("{ANSI_YELLOW}.{ANSI_RESET}{ANSI_YELLOW}.{ANSI_RESET}.\n...\n"));
+    }
+
+    #[test]
+    fn replay_options_test() {
+        let options = |fps: &str| ["--replay", "--fps", fps].map(String::from);
+
+        assert_eq!(ReplayOptions::from_options(&options("12.5")).map(|replay_options| replay_options.fps), Ok(12.5));
+        assert_eq!(ReplayOptions::from_options(&[]).map(|replay_options| replay_options.fps), Ok(30.0));
+
+        for fps in ["0", "-5", "inf", "NaN", "fast"] {
+            assert_eq!(
+                ReplayOptions::from_options(&options(fps)).map(|replay_options| replay_options.fps),
+                Err(format!("--fps should be a positive number but is {fps}")),
+            );
+        }
+    }
+
+    #[test]
+    fn image_test() {
+        let patrol_matrix = PatrolMatrix::new("#.\n^.\n..").unwrap();
+
+        let cells = image_cells(&patrol_matrix, &[(1, 1)]);
+        assert_eq!(cells, vec![
+            ImageCell::Obstacle, ImageCell::Unvisited,
+            ImageCell::GuardStart, ImageCell::LoopObstacle,
+            ImageCell::Unvisited, ImageCell::Unvisited,
+        ]);
+
+        let mut image = Vec::new();
+        write_ppm(&cells[..2], 2, 2, &mut image).unwrap();
+
+        let header = "P6\n\
+            # unvisited: 255 255 255\n\
+            # visited: 120 170 255\n\
+            # obstacle: 40 40 40\n\
+            # guard start: 0 170 0\n\
+            # loop obstacle: 220 30 30\n\
+            4 2\n255\n";
+        let row = [[40, 40, 40], [40, 40, 40], [255, 255, 255], [255, 255, 255]].concat();
+        assert_eq!(image, [header.as_bytes(), &row, &row].concat());
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 5305);
@@ -1,9 +1,12 @@
 use std::fs;
 use std::ops::Add;
 use itertools::Itertools;
+use crate::grid;
+use crate::grid::GridError;
 
 pub fn part_one() -> isize {
-    let terrain_map = TerrainMap::new("inputs/day_ten_input.txt");
+    let terrain_map = TerrainMap::new("inputs/day_ten_input.txt")
+        .expect("Should have been able to parse the grid");
 
     terrain_map
         .iter()
@@ -17,7 +20,8 @@ pub fn part_one() -> isize {
 }
 
 pub fn part_two() -> isize {
-    let terrain_map = TerrainMap::new("inputs/day_ten_input.txt");
+    let terrain_map = TerrainMap::new("inputs/day_ten_input.txt")
+        .expect("Should have been able to parse the grid");
 
     terrain_map
         .iter()
@@ -63,24 +67,35 @@ struct TerrainMap {
 }
 
 impl TerrainMap {
-    fn new(file: &str) -> TerrainMap {
+    fn new(file: &str) -> Result<TerrainMap, GridError> {
         let content = fs::read_to_string(file)
             .expect("Should have been able to read the file");
 
-        let height = content.chars().filter(|c| *c == '\n').count() as isize + 1;
-        let width = content.find('\n').unwrap() as isize;
-
-        let tiles = content
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .map(|c| c.to_digit(10).unwrap() as isize)
-            .collect::<Vec<isize>>();
+        TerrainMap::parse(&content)
+    }
 
-        TerrainMap {
+    fn parse(content: &str) -> Result<TerrainMap, GridError> {
+        let grid = grid::parse_rows(content)?;
+
+        let tiles = grid.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(column, cell)| {
+                        cell.to_digit(10)
+                            .map(|height| height as isize)
+                            .ok_or(GridError::InvalidCell { row, column, cell })
+                    })
+            })
+            .collect::<Result<Vec<isize>, GridError>>()?;
+
+        Ok(TerrainMap {
             tiles,
-            height,
-            width,
-        }
+            height: grid.height,
+            width: grid.width,
+        })
     }
 
     fn get(&self, position: &Position) -> Option<isize> {
@@ -208,6 +223,13 @@ impl Iterator for TerrainMapIterator<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn invalid_cell_test() {
+        assert_eq!(TerrainMap::parse("012\n3.5").err(), Some(GridError::InvalidCell { row: 1, column: 1, cell: '.' }));
+        assert_eq!(TerrainMap::parse("012\n345").map(|map| map.tiles).ok(), Some(vec![0, 1, 2, 3, 4, 5]));
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 638);
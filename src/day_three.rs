@@ -1,5 +1,18 @@
+use std::fmt::{Display, Formatter, Write};
 use std::fs;
+use std::ops::{Add, Mul, Neg};
+use std::time::Instant;
 use regex::Regex;
+use crate::big_int::BigInt;
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_GREEN: &str = "\x1b[1;32m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_YELLOW: &str = "\x1b[1;33m";
+const ANSI_DIM: &str = "\x1b[2m";
+
+// the puzzle only allows operands of 1 to 3 digits
+const PUZZLE_MAX_DIGITS: usize = 3;
 
 const MULTIPLY_REGEX: &str = r"mul\(([0-9]+),([0-9]+)\)";
 const ALL_REGEX: &str = r"(mul\([0-9]+,[0-9]+\)|do\(\)|don't\(\))";
@@ -8,9 +21,601 @@ pub fn part_one() -> isize {
     let contents = fs::read_to_string("inputs/day_three_input.txt")
         .expect("Should have been able to read the file");
 
+    let sum = sum_instructions(&contents, &InstructionSet::part_one(), None);
+
+    println!("Sum of multiplications: {sum}");
+    sum.to_isize().expect("The sum should fit in an isize")
+}
+
+pub fn part_two() -> isize {
+    let contents = fs::read_to_string("inputs/day_three_input.txt")
+        .expect("Should have been able to read the file");
+
+    let sum = sum_instructions(&contents, &InstructionSet::part_two(), None);
+
+    println!("Sum of enabled multiplications: {sum}");
+    sum.to_isize().expect("The sum should fit in an isize")
+}
+
+pub fn run(options: &[String]) {
+    let contents = fs::read_to_string("inputs/day_three_input.txt")
+        .expect("Should have been able to read the file");
+
+    let instructions_file = crate::option_value(options, "--instructions");
+    if instructions_file.is_none() && options.iter().any(|option| option == "--instructions") {
+        eprintln!("--instructions should be followed by a file");
+        return
+    }
+
+    // part one doesn't know about do() and don't(), so only trace them when asked about part two.
+    // A custom instruction set replaces both.
+    let instruction_set = match instructions_file {
+        Some(file) => InstructionSet::from_file(file),
+        None if options.iter().any(|option| option == "--part-one") => InstructionSet::part_one(),
+        None => InstructionSet::part_two(),
+    };
+
+    // --strict enforces the puzzle's 1 to 3 digit operand rule on everything below and reports what
+    // it rejected, so a trace always adds up to the sums printed after it
+    let max_digits = match options.iter().any(|option| option == "--strict") {
+        true => Some(PUZZLE_MAX_DIGITS),
+        false => None,
+    };
+
+    if options.iter().any(|option| option == "--trace") {
+        let lexer = Lexer::new(&contents, &instruction_set).with_max_digits(max_digits);
+        for entry in Interpreter::new(&instruction_set).trace(lexer) {
+            println!("{}", entry.describe(&contents));
+        }
+    }
+
+    if max_digits.is_some() {
+        let mut lexer = Lexer::new(&contents, &instruction_set).with_max_digits(max_digits);
+        lexer.by_ref().for_each(drop);
+
+        for rejection in lexer.rejected {
+            println!("{}", rejection.describe(&contents));
+        }
+    }
+
+    if options.iter().any(|option| option == "--annotate") {
+        println!("{}", annotate(&contents, &instruction_set, max_digits));
+    }
+
+    if options.iter().any(|option| option == "--bench") {
+        benchmark();
+    }
+
+    if instructions_file.is_some() {
+        println!("Sum with custom instructions: {}", sum_instructions(&contents, &instruction_set, max_digits));
+        return
+    }
+
+    if max_digits.is_none() {
+        println!("Part one: {}", part_one());
+        println!("Part two: {}", part_two());
+        return
+    }
+
+    println!("Part one: {}", sum_instructions(&contents, &InstructionSet::part_one(), max_digits));
+    println!("Part two: {}", sum_instructions(&contents, &InstructionSet::part_two(), max_digits));
+}
+
+// run every instruction the lexer finds, rejecting operands longer than max_digits
+fn sum_instructions(contents: &str, instruction_set: &InstructionSet, max_digits: Option<usize>) -> Number {
+    let lexer = Lexer::new(contents, instruction_set).with_max_digits(max_digits);
+    Interpreter::new(instruction_set).run(lexer)
+}
+
+type Semantics = fn(&mut Machine, &[Number]);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InstructionKind {
+    // only runs while the machine is enabled
+    Operation,
+    // always runs, these are for instructions that change the machine state like do() and don't()
+    Control,
+}
+
+#[derive(Debug, Clone)]
+struct InstructionDefinition {
+    pub name: String,
+    pub arity: usize,
+    pub kind: InstructionKind,
+    pub semantics: Semantics,
+}
+
+// the instructions the lexer will recognize and how the interpreter runs them
+#[derive(Debug, Clone, Default)]
+struct InstructionSet {
+    instructions: Vec<InstructionDefinition>,
+}
+
+impl InstructionSet {
+    fn new() -> InstructionSet {
+        InstructionSet::default()
+    }
+
+    fn part_one() -> InstructionSet {
+        let mut instruction_set = InstructionSet::new();
+        instruction_set.register("mul", 2, InstructionKind::Operation, multiply);
+        instruction_set
+    }
+
+    fn part_two() -> InstructionSet {
+        let mut instruction_set = InstructionSet::part_one();
+        instruction_set.register("do", 0, InstructionKind::Control, enable);
+        instruction_set.register("don't", 0, InstructionKind::Control, disable);
+        instruction_set
+    }
+
+    fn from_file(file: &str) -> InstructionSet {
+        let contents = fs::read_to_string(file)
+            .expect("Should have been able to read the file");
+
+        InstructionSet::parse(&contents)
+    }
+
+    // parse a config with an instruction per line in the form `name arity semantics`, for example
+    // `mul 2 multiply`. Blank lines and lines starting with # are ignored.
+    fn parse(config: &str) -> InstructionSet {
+        let mut instruction_set = InstructionSet::new();
+
+        for line in config.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+
+            let parts = line.split_whitespace().collect::<Vec<&str>>();
+            let [name, arity, semantics] = parts[..] else {
+                panic!("Instruction should be in the form `name arity semantics`: {line}")
+            };
+            let (kind, semantics) = semantics_by_name(semantics)
+                .unwrap_or_else(|| panic!("Unknown instruction semantics: {semantics}"));
+
+            instruction_set.register(name, arity.parse().unwrap(), kind, semantics);
+        }
+
+        instruction_set
+    }
+
+    // add an instruction, replacing any instruction that already has the name
+    fn register(&mut self, name: &str, arity: usize, kind: InstructionKind, semantics: Semantics) {
+        let definition = InstructionDefinition {
+            name: name.to_string(),
+            arity,
+            kind,
+            semantics,
+        };
+
+        match self.instructions.iter_mut().find(|instruction| instruction.name == name) {
+            Some(instruction) => *instruction = definition,
+            None => self.instructions.push(definition),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&InstructionDefinition> {
+        self.instructions.iter().find(|instruction| instruction.name == name)
+    }
+}
+
+// the semantics that can be given to instructions in an instruction set config
+fn semantics_by_name(name: &str) -> Option<(InstructionKind, Semantics)> {
+    match name {
+        "multiply" => Some((InstructionKind::Operation, multiply)),
+        "add" => Some((InstructionKind::Operation, add)),
+        "negate" => Some((InstructionKind::Operation, negate)),
+        "enable" => Some((InstructionKind::Control, enable)),
+        "disable" => Some((InstructionKind::Control, disable)),
+        "push_scope" => Some((InstructionKind::Control, push_scope)),
+        "pop_scope" => Some((InstructionKind::Control, pop_scope)),
+        _ => None,
+    }
+}
+
+// add the product of every argument to the sum
+fn multiply(machine: &mut Machine, args: &[Number]) {
+    let product = args.iter().fold(Number::Small(1), |product, arg| &product * arg);
+    machine.sum = &machine.sum + &product;
+}
+
+// add every argument to the sum
+fn add(machine: &mut Machine, args: &[Number]) {
+    machine.sum = args.iter().fold(machine.sum.clone(), |sum, arg| &sum + arg);
+}
+
+// subtract every argument from the sum
+fn negate(machine: &mut Machine, args: &[Number]) {
+    machine.sum = args.iter().fold(machine.sum.clone(), |sum, arg| &sum + &-arg);
+}
+
+fn enable(machine: &mut Machine, _args: &[Number]) {
+    machine.enabled = true;
+}
+
+fn disable(machine: &mut Machine, _args: &[Number]) {
+    machine.enabled = false;
+}
+
+// remember if the machine is enabled so that the matching pop_scope can restore it, letting
+// toggles inside the scope only apply until the scope ends
+fn push_scope(machine: &mut Machine, _args: &[Number]) {
+    machine.scopes.push(machine.enabled);
+}
+
+fn pop_scope(machine: &mut Machine, _args: &[Number]) {
+    if let Some(enabled) = machine.scopes.pop() {
+        machine.enabled = enabled;
+    }
+}
+
+// an instruction found in the corrupted memory along with where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token<'a> {
+    pub name: &'a str,
+    pub args: Vec<Number>,
+    pub offset: usize,
+    pub length: usize,
+}
+
+// an instruction that was well formed but broke the operand digit limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rejection {
+    pub offset: usize,
+    pub length: usize,
+    pub reason: String,
+}
+
+impl Rejection {
+    fn describe(&self, contents: &str) -> String {
+        let text = &contents[self.offset..self.offset + self.length];
+
+        format!("{:>6}  {:<14} rejected: {}", self.offset, text, self.reason)
+    }
+}
+
+// walks the corrupted memory one byte at a time, yielding every well formed instruction from the
+// instruction set and skipping everything else
+struct Lexer<'a> {
+    input: &'a [u8],
+    instruction_set: &'a InstructionSet,
+    position: usize,
+    // operands with more digits than this are rejected, no limit when None
+    pub max_digits: Option<usize>,
+    pub rejected: Vec<Rejection>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, instruction_set: &'a InstructionSet) -> Lexer<'a> {
+        Lexer {
+            input: input.as_bytes(),
+            instruction_set,
+            position: 0,
+            max_digits: None,
+            rejected: Vec::new(),
+        }
+    }
+
+    fn with_max_digits(self, max_digits: Option<usize>) -> Lexer<'a> {
+        Lexer {
+            max_digits,
+            ..self
+        }
+    }
+
+    // the token at `offset`, or a rejection if there is a well formed instruction there whose
+    // operands are too long
+    fn token_at(&self, offset: usize) -> Option<Result<Token<'a>, Rejection>> {
+        self.instruction_set.instructions.iter().find_map(|instruction| {
+            let mut position = offset;
+
+            position = self.expect(position, instruction.name.as_bytes())?;
+            position = self.expect(position, b"(")?;
+
+            let mut args = Vec::with_capacity(instruction.arity);
+            let mut longest = 0;
+            for i in 0..instruction.arity {
+                if i > 0 {
+                    position = self.expect(position, b",")?;
+                }
+                let (arg, next) = self.number(position)?;
+                args.push(arg);
+                longest = longest.max(next - position);
+                position = next;
+            }
+
+            position = self.expect(position, b")")?;
+            let length = position - offset;
+
+            match self.max_digits {
+                Some(max_digits) if longest > max_digits => Some(Err(Rejection {
+                    offset,
+                    length,
+                    reason: format!("operand has {longest} digits, the limit is {max_digits}"),
+                })),
+                _ => Some(Ok(Token {
+                    name: &instruction.name,
+                    args,
+                    offset,
+                    length,
+                })),
+            }
+        })
+    }
+
+    // returns the position after `expected` if the input has it at `position`
+    fn expect(&self, position: usize, expected: &[u8]) -> Option<usize> {
+        match self.input[position..].starts_with(expected) {
+            true => Some(position + expected.len()),
+            false => None,
+        }
+    }
+
+    // parses the run of digits at `position`, returning the number and the position after it
+    fn number(&self, position: usize) -> Option<(Number, usize)> {
+        let digits = self.input[position..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+
+        if digits == 0 {
+            return None
+        }
+
+        Some((Number::parse(&self.input[position..position + digits]), position + digits))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.input.len() {
+            match self.token_at(self.position) {
+                Some(Ok(token)) => {
+                    self.position += token.length;
+                    return Some(token)
+                },
+                // keep scanning inside a rejected instruction, the same as if it never matched
+                Some(Err(rejection)) => {
+                    self.rejected.push(rejection);
+                    self.position += 1;
+                },
+                None => self.position += 1,
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Machine {
+    pub enabled: bool,
+    pub scopes: Vec<bool>,
+    pub sum: Number,
+}
+
+struct Interpreter<'a> {
+    instruction_set: &'a InstructionSet,
+    pub machine: Machine,
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(instruction_set: &'a InstructionSet) -> Interpreter<'a> {
+        Interpreter {
+            instruction_set,
+            machine: Machine {
+                enabled: true,
+                scopes: Vec::new(),
+                sum: Number::Small(0),
+            },
+        }
+    }
+
+    fn run<'t>(&mut self, tokens: impl Iterator<Item = Token<'t>>) -> Number {
+        for token in tokens {
+            self.execute(&token);
+        }
+        self.machine.sum.clone()
+    }
+
+    // run the tokens, recording the state after every instruction
+    fn trace<'t>(&mut self, tokens: impl Iterator<Item = Token<'t>>) -> Vec<TraceEntry<'t>> {
+        tokens
+            .map(|token| {
+                let kind = self.execute(&token);
+
+                TraceEntry {
+                    kind,
+                    enabled: self.machine.enabled,
+                    sum: self.machine.sum.clone(),
+                    token,
+                }
+            })
+            .collect()
+    }
+
+    fn execute(&mut self, token: &Token) -> InstructionKind {
+        let instruction = self.instruction_set
+            .get(token.name)
+            .unwrap_or_else(|| panic!("Unknown instruction: {}", token.name));
+
+        if instruction.kind == InstructionKind::Control || self.machine.enabled {
+            (instruction.semantics)(&mut self.machine, &token.args);
+        }
+
+        instruction.kind
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceEntry<'a> {
+    pub token: Token<'a>,
+    pub kind: InstructionKind,
+    pub enabled: bool,
+    pub sum: Number,
+}
+
+impl TraceEntry<'_> {
+    fn describe(&self, contents: &str) -> String {
+        let text = &contents[self.token.offset..self.token.offset + self.token.length];
+        let state = match self.enabled {
+            true => "enabled",
+            false => "disabled",
+        };
+
+        format!("{:>6}  {:<14} {:<9} {}", self.token.offset, text, state, self.sum)
+    }
+}
+
+// a number that starts out as an isize and switches to a BigInt when it would overflow. A Big
+// only ever holds a value that doesn't fit in an isize, so equal values always compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Number {
+    Small(isize),
+    Big(BigInt),
+}
+
+impl Number {
+    // parse a run of ascii digits
+    fn parse(digits: &[u8]) -> Number {
+        let small = digits.iter().try_fold(0isize, |value, digit| {
+            value.checked_mul(10)?.checked_add((digit - b'0') as isize)
+        });
+
+        match small {
+            Some(value) => Number::Small(value),
+            None => Number::Big(BigInt::parse(digits)),
+        }
+    }
+
+    // switch back to an isize as soon as the value fits in one again
+    fn from_big_int(value: BigInt) -> Number {
+        match value.to_isize() {
+            Some(value) => Number::Small(value),
+            None => Number::Big(value),
+        }
+    }
+
+    fn to_big_int(&self) -> BigInt {
+        match self {
+            Number::Small(value) => BigInt::from_isize(*value),
+            Number::Big(value) => value.clone(),
+        }
+    }
+
+    fn to_isize(&self) -> Option<isize> {
+        match self {
+            Number::Small(value) => Some(*value),
+            Number::Big(value) => value.to_isize(),
+        }
+    }
+}
+
+impl Add for &Number {
+    type Output = Number;
+
+    fn add(self, rhs: &Number) -> Self::Output {
+        if let (Number::Small(lhs), Number::Small(rhs)) = (self, rhs) {
+            if let Some(value) = lhs.checked_add(*rhs) {
+                return Number::Small(value)
+            }
+        }
+        Number::from_big_int(&self.to_big_int() + &rhs.to_big_int())
+    }
+}
+
+impl Mul for &Number {
+    type Output = Number;
+
+    fn mul(self, rhs: &Number) -> Self::Output {
+        if let (Number::Small(lhs), Number::Small(rhs)) = (self, rhs) {
+            if let Some(value) = lhs.checked_mul(*rhs) {
+                return Number::Small(value)
+            }
+        }
+        Number::from_big_int(&self.to_big_int() * &rhs.to_big_int())
+    }
+}
+
+impl Neg for &Number {
+    type Output = Number;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Number::Small(value) => match value.checked_neg() {
+                Some(value) => Number::Small(value),
+                None => Number::Big(-&BigInt::from_isize(*value)),
+            },
+            Number::Big(value) => Number::from_big_int(-value),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Small(value) => write!(f, "{value}"),
+            Number::Big(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+// render the corrupted memory with every recognized instruction colored. Operations that ran are
+// green and ones that were disabled are red, control instructions like do() and don't() are
+// yellow, and the corrupted bytes in between are dimmed. Instructions with operands longer than
+// max_digits never run, so they are dimmed along with the corruption.
+fn annotate(contents: &str, instruction_set: &InstructionSet, max_digits: Option<usize>) -> String {
+    let mut annotated = String::new();
+    let mut position = 0;
+
+    let lexer = Lexer::new(contents, instruction_set).with_max_digits(max_digits);
+    for entry in Interpreter::new(instruction_set).trace(lexer) {
+        let (offset, length) = (entry.token.offset, entry.token.length);
+
+        let color = match entry.kind {
+            InstructionKind::Operation if entry.enabled => ANSI_GREEN,
+            InstructionKind::Operation => ANSI_RED,
+            InstructionKind::Control => ANSI_YELLOW,
+        };
+
+        write!(annotated, "{ANSI_DIM}{}{ANSI_RESET}", &contents[position..offset]).unwrap();
+        write!(annotated, "{color}{}{ANSI_RESET}", &contents[offset..offset + length]).unwrap();
+        position = offset + length;
+    }
+    write!(annotated, "{ANSI_DIM}{}{ANSI_RESET}", &contents[position..]).unwrap();
+
+    annotated
+}
+
+// run the regex and lexer implementations against each other and print how long each takes
+pub fn benchmark() {
+    let contents = fs::read_to_string("inputs/day_three_input.txt")
+        .expect("Should have been able to read the file");
+    let runs = 100;
+
+    let time = |name: &str, solve: &dyn Fn(&str) -> isize| {
+        let now = Instant::now();
+        for _ in 0..runs {
+            solve(&contents);
+        }
+        println!("{name}: {:.2?} per run", now.elapsed() / runs);
+    };
+
+    let lexer = |contents: &str, instruction_set: &InstructionSet| {
+        sum_instructions(contents, instruction_set, None).to_isize().unwrap()
+    };
+
+    time("part one (regex)", &part_one_regex);
+    time("part one (lexer)", &|contents| lexer(contents, &InstructionSet::part_one()));
+    time("part two (regex)", &part_two_regex);
+    time("part two (lexer)", &|contents| lexer(contents, &InstructionSet::part_two()));
+}
+
+fn part_one_regex(contents: &str) -> isize {
     let re = Regex::new(MULTIPLY_REGEX).unwrap();
 
-    let captures = re.captures_iter(&*contents).map(|x| x.extract());
+    let captures = re.captures_iter(contents).map(|x| x.extract());
 
     captures
         .map(|capture| {
@@ -20,13 +625,10 @@ pub fn part_one() -> isize {
         .sum()
 }
 
-pub fn part_two() -> isize {
-    let contents = fs::read_to_string("inputs/day_three_input.txt")
-        .expect("Should have been able to read the file");
-
+fn part_two_regex(contents: &str) -> isize {
     let all_regex = Regex::new(ALL_REGEX).unwrap();
 
-    let captures = all_regex.captures_iter(&*contents).map(|x| x.extract());
+    let captures = all_regex.captures_iter(contents).map(|x| x.extract());
 
     let mut enabled = true;
     let mut sum = 0isize;
@@ -60,6 +662,178 @@ fn process_multiply(string: &str) -> isize {
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    fn solve(contents: &str, instruction_set: &InstructionSet) -> isize {
+        solve_big(contents, instruction_set).to_isize().unwrap()
+    }
+
+    fn solve_big(contents: &str, instruction_set: &InstructionSet) -> Number {
+        Interpreter::new(instruction_set).run(Lexer::new(contents, instruction_set))
+    }
+
+    #[test]
+    fn lexer_test() {
+        let instruction_set = InstructionSet::part_two();
+        let tokens = Lexer::new(EXAMPLE, &instruction_set)
+            .map(|token| (token.name, token.args, token.offset, token.length))
+            .collect::<Vec<(&str, Vec<Number>, usize, usize)>>();
+
+        let small = |values: &[isize]| values.iter().map(|x| Number::Small(*x)).collect();
+        assert_eq!(tokens, vec![
+            ("mul", small(&[2, 4]), 1, 8),
+            ("don't", small(&[]), 20, 7),
+            ("mul", small(&[5, 5]), 28, 8),
+            ("mul", small(&[11, 8]), 48, 9),
+            ("do", small(&[]), 59, 4),
+            ("mul", small(&[8, 5]), 64, 8),
+        ]);
+    }
+
+    #[test]
+    fn interpreter_test() {
+        assert_eq!(solve(EXAMPLE, &InstructionSet::part_one()), 161);
+        assert_eq!(solve(EXAMPLE, &InstructionSet::part_two()), 48);
+    }
+
+    #[test]
+    fn register_test() {
+        let mut instruction_set = InstructionSet::part_two();
+        instruction_set.register("add", 2, InstructionKind::Operation, add);
+        instruction_set.register("neg", 1, InstructionKind::Operation, negate);
+
+        assert_eq!(solve("add(1,2)mul(3,4)neg(5)don't()add(100,100)neg(1,2)", &instruction_set), 10);
+
+        // registering an existing name replaces it
+        instruction_set.register("mul", 2, InstructionKind::Operation, add);
+        assert_eq!(solve("mul(3,4)", &instruction_set), 7);
+    }
+
+    #[test]
+    fn scope_test() {
+        let instruction_set = InstructionSet::parse("
+            # toggles inside a scope only last until the scope ends
+            mul 2 multiply
+            do 0 enable
+            don't 0 disable
+            begin 0 push_scope
+            end 0 pop_scope
+        ");
+
+        assert_eq!(solve("begin()don't()mul(2,2)end()mul(3,3)", &instruction_set), 9);
+        assert_eq!(solve("don't()begin()do()mul(2,2)end()mul(3,3)", &instruction_set), 4);
+    }
+
+    #[test]
+    fn overflow_test() {
+        let instruction_set = InstructionSet::part_one();
+
+        let sum = solve_big("mul(9999999999,9999999999)mul(1,1)", &instruction_set);
+        assert_eq!(sum.to_string(), "99999999980000000002");
+        assert_eq!(sum.to_isize(), None);
+
+        let sum = solve_big("mul(123456789012345678901234567890,10)", &instruction_set);
+        assert_eq!(sum.to_string(), "1234567890123456789012345678900");
+
+        // going back into range still gives the right answer
+        let mut instruction_set = InstructionSet::part_one();
+        instruction_set.register("neg", 1, InstructionKind::Operation, negate);
+        let sum = solve_big("mul(9223372036854775807,2)neg(9223372036854775807)neg(9223372036854775000)", &instruction_set);
+        assert_eq!(sum, Number::Small(807));
+        let sum = solve_big("neg(9223372036854775807)neg(9223372036854775807)", &instruction_set);
+        assert_eq!(sum.to_string(), "-18446744073709551614");
+    }
+
+    #[test]
+    fn number_equality_test() {
+        let max = Number::Small(isize::MAX);
+        let overflowed = &max + &Number::Small(1);
+        assert!(matches!(overflowed, Number::Big(_)));
+
+        // coming back into range gives the same number as never having left it
+        assert_eq!(&overflowed + &Number::Small(-1), max);
+        assert_eq!(-&(-&overflowed), overflowed);
+        assert_eq!(-&Number::Small(isize::MIN), overflowed);
+        assert_eq!(-&overflowed, Number::Small(isize::MIN));
+        assert_eq!(&overflowed * &Number::Small(0), Number::Small(0));
+    }
+
+    #[test]
+    fn max_digits_test() {
+        let instruction_set = InstructionSet::part_one();
+        let contents = "mul(1,2)mul(1234,5)mul(12,345)";
+
+        let mut lexer = Lexer::new(contents, &instruction_set).with_max_digits(Some(PUZZLE_MAX_DIGITS));
+        assert_eq!(Interpreter::new(&instruction_set).run(&mut lexer), Number::Small(4142));
+        assert_eq!(lexer.rejected, vec![Rejection {
+            offset: 8,
+            length: 11,
+            reason: "operand has 4 digits, the limit is 3".to_string(),
+        }]);
+        assert_eq!(
+            lexer.rejected[0].describe(contents),
+            "     8  mul(1234,5)    rejected: operand has 4 digits, the limit is 3",
+        );
+
+        assert_eq!(solve(contents, &instruction_set), 10312);
+    }
+
+    #[test]
+    fn trace_test() {
+        let instruction_set = InstructionSet::part_two();
+        let trace = Interpreter::new(&instruction_set).trace(Lexer::new(EXAMPLE, &instruction_set));
+
+        let states = trace
+            .iter()
+            .map(|entry| (entry.token.offset, entry.enabled, entry.sum.to_isize().unwrap()))
+            .collect::<Vec<(usize, bool, isize)>>();
+
+        assert_eq!(states, vec![
+            (1, true, 8),
+            (20, false, 8),
+            (28, false, 8),
+            (48, false, 8),
+            (59, true, 8),
+            (64, true, 48),
+        ]);
+        assert_eq!(trace[0].describe(EXAMPLE), "     1  mul(2,4)       enabled   8");
+    }
+
+    #[test]
+    fn annotate_test() {
+        let annotated = annotate("xmul(2,4)don't()mul(1,1)", &InstructionSet::part_two(), None);
+
+        assert_eq!(annotated, format!(
+            "{ANSI_DIM}x{ANSI_RESET}{ANSI_GREEN}mul(2,4){ANSI_RESET}\
+            {ANSI_DIM}{ANSI_RESET}{ANSI_YELLOW}don't(){ANSI_RESET}\
+            {ANSI_DIM}{ANSI_RESET}{ANSI_RED}mul(1,1){ANSI_RESET}\
+            {ANSI_DIM}{ANSI_RESET}"
+        ));
+    }
+
+    #[test]
+    fn strict_annotate_test() {
+        let instruction_set = InstructionSet::part_one();
+        let contents = "mul(1234,5)mul(2,3)";
+
+        assert_eq!(annotate(contents, &instruction_set, Some(PUZZLE_MAX_DIGITS)), format!(
+            "{ANSI_DIM}mul(1234,5){ANSI_RESET}{ANSI_GREEN}mul(2,3){ANSI_RESET}{ANSI_DIM}{ANSI_RESET}"
+        ));
+        assert_eq!(annotate(contents, &instruction_set, None), format!(
+            "{ANSI_DIM}{ANSI_RESET}{ANSI_GREEN}mul(1234,5){ANSI_RESET}\
+            {ANSI_DIM}{ANSI_RESET}{ANSI_GREEN}mul(2,3){ANSI_RESET}{ANSI_DIM}{ANSI_RESET}"
+        ));
+    }
+
+    #[test]
+    fn matches_regex_test() {
+        let contents = fs::read_to_string("inputs/day_three_input.txt")
+            .expect("Should have been able to read the file");
+
+        assert_eq!(solve(&contents, &InstructionSet::part_one()), part_one_regex(&contents));
+        assert_eq!(solve(&contents, &InstructionSet::part_two()), part_two_regex(&contents));
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 164_730_528);
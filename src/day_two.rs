@@ -1,63 +1,239 @@
 use std::fs;
+use itertools::Itertools;
 
 pub fn part_one() -> usize {
-    // get the lists from the txt file
-    let contents = fs::read_to_string("inputs/day_two_input.txt")
+    let safe_report_count = count_safe_reports("inputs/day_two_input.txt", &SafetyRules::part_one());
+
+    println!("Safe report count: {safe_report_count}");
+    safe_report_count
+}
+
+pub fn part_two() -> usize {
+    let safe_report_count = count_safe_reports("inputs/day_two_input.txt", &SafetyRules::part_two());
+
+    println!("Safe report count: {safe_report_count}");
+    safe_report_count
+}
+
+pub fn run(options: &[String]) {
+    if let Some(file) = crate::option_value(options, "--rules") {
+        let rules = SafetyRules::from_file(file);
+        let safe_report_count = count_safe_reports("inputs/day_two_input.txt", &rules);
+
+        println!("Safe report count: {safe_report_count}");
+        return
+    }
+
+    println!("Part one: {}", part_one());
+    println!("Part two: {}", part_two());
+}
+
+fn count_safe_reports(file: &str, rules: &SafetyRules) -> usize {
+    process_reports(file)
+        .iter()
+        // calculate if the report is safe, filter out unsafe reports, and count the remaining safe
+        // ones
+        .filter(|report| rules.is_safe(report))
+        .count()
+}
+
+fn process_reports(file: &str) -> Vec<Vec<isize>> {
+    let contents = fs::read_to_string(file)
         .expect("Should have been able to read the file");
 
-    let safe_report_count = contents
-        // split into lines
-        .split("\n")
+    parse_reports(&contents)
+}
+
+fn parse_reports(contents: &str) -> Vec<Vec<isize>> {
+    contents
+        // split into lines, skipping blank ones so they don't turn into empty reports
+        .lines()
+        .filter(|line| !line.trim().is_empty())
         // split and parse numbers collecting a Vec<isize>
         .map(|x| {
             x.split_whitespace()
                 .map(|y| y.parse::<isize>().unwrap())
                 .collect::<Vec<isize>>()
         })
-        // calculate if the report is safe, filter out unsafe reports, and count the remaining safe
-        // ones
-        .map(|report| {
-            is_safe(&report)
-        }).filter(|x| *x)
-        .count();
+        .collect()
+}
 
-    println!("Safe report count: {safe_report_count}");
-    safe_report_count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Monotonicity {
+    Increasing,
+    Decreasing,
+    // the report has to be all increasing or all decreasing, but can be either
+    Either,
+    // the report can change direction freely
+    Any,
+}
+
+impl Monotonicity {
+    fn parse(value: &str) -> Monotonicity {
+        match value {
+            "increasing" => Monotonicity::Increasing,
+            "decreasing" => Monotonicity::Decreasing,
+            "either" => Monotonicity::Either,
+            "any" => Monotonicity::Any,
+            _ => panic!("Unknown monotonicity mode: {value}"),
+        }
+    }
+}
+
+// A safety policy for reports. Every pair of adjacent levels has to differ by between min_delta
+// and max_delta (inclusive) and follow the monotonicity mode. Adjacent levels that are equal are
+// plateaus, which are only allowed when allow_plateaus is set. tolerance is how many levels can be
+// removed from a report to make it safe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SafetyRules {
+    pub min_delta: isize,
+    pub max_delta: isize,
+    pub monotonicity: Monotonicity,
+    pub allow_plateaus: bool,
+    pub tolerance: usize,
+}
+
+impl SafetyRules {
+    fn part_one() -> SafetyRules {
+        SafetyRules {
+            min_delta: 1,
+            max_delta: 3,
+            monotonicity: Monotonicity::Either,
+            allow_plateaus: false,
+            tolerance: 0,
+        }
+    }
+
+    fn part_two() -> SafetyRules {
+        SafetyRules {
+            tolerance: 1,
+            ..SafetyRules::part_one()
+        }
+    }
+
+    fn from_file(file: &str) -> SafetyRules {
+        let contents = fs::read_to_string(file)
+            .expect("Should have been able to read the file");
+
+        SafetyRules::parse(&contents)
+    }
+
+    // parse a config made of `key = value` lines. Blank lines and lines starting with # are
+    // ignored, and any key that isn't given keeps the part one value.
+    fn parse(config: &str) -> SafetyRules {
+        let mut rules = SafetyRules::part_one();
+
+        for line in config.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .unwrap_or_else(|| panic!("Rule should be in the form `key = value`: {line}"));
+            let value = value.trim();
+
+            match key.trim() {
+                "min_delta" => rules.min_delta = value.parse().unwrap(),
+                "max_delta" => rules.max_delta = value.parse().unwrap(),
+                "monotonicity" => rules.monotonicity = Monotonicity::parse(value),
+                "allow_plateaus" => rules.allow_plateaus = value.parse().unwrap(),
+                "tolerance" => rules.tolerance = value.parse().unwrap(),
+                key => panic!("Unknown rule: {key}"),
+            }
+        }
+
+        rules
+    }
+
+    fn is_safe(&self, report: &[isize]) -> bool {
+        // try every report with up to `tolerance` levels removed. combinations keeps the levels
+        // in their original order.
+        (0..=self.tolerance.min(report.len()))
+            .any(|removed| {
+                report
+                    .iter()
+                    .copied()
+                    .combinations(report.len() - removed)
+                    .any(|report| self.is_strictly_safe(&report))
+            })
+    }
+
+    fn is_strictly_safe(&self, report: &[isize]) -> bool {
+        self.is_monotonic(report) && self.is_delta_allowed(report)
+    }
+
+    fn is_monotonic(&self, report: &[isize]) -> bool {
+        let increasing = || match self.allow_plateaus {
+            true => is_non_decreasing(report),
+            false => is_increasing(report),
+        };
+        let decreasing = || match self.allow_plateaus {
+            true => is_non_increasing(report),
+            false => is_decreasing(report),
+        };
+
+        match self.monotonicity {
+            Monotonicity::Increasing => increasing(),
+            Monotonicity::Decreasing => decreasing(),
+            Monotonicity::Either => increasing() || decreasing(),
+            Monotonicity::Any => true,
+        }
+    }
+
+    fn is_delta_allowed(&self, report: &[isize]) -> bool {
+        compare_to_previous(
+            report,
+            |value, previous| {
+                match (value - previous).abs() {
+                    0 => self.allow_plateaus,
+                    delta => self.min_delta <= delta && delta <= self.max_delta,
+                }
+            })
+    }
 }
 
-fn is_safe(report: &Vec<isize>) -> bool {
-    (is_increasing(report) || is_decreasing(report)) && is_small_delta(report)
+fn is_increasing(report: &[isize]) -> bool {
+    compare_to_previous(
+        report,
+        |value, previous| {
+        value > previous
+    })
 }
 
-fn is_small_delta(report: &Vec<isize>) -> bool {
+fn is_decreasing(report: &[isize]) -> bool {
     compare_to_previous(
         report,
         |value, previous| {
-            (value - previous).abs() <= 3
+            value < previous
         })
 }
 
-fn is_increasing(report: &Vec<isize>) -> bool {
+fn is_non_decreasing(report: &[isize]) -> bool {
     compare_to_previous(
         report,
         |value, previous| {
-        value > previous
-    })
+            value >= previous
+        })
 }
 
-fn is_decreasing(report: &Vec<isize>) -> bool {
+fn is_non_increasing(report: &[isize]) -> bool {
     compare_to_previous(
         report,
         |value, previous| {
-            value < previous
+            value <= previous
         })
 }
 
-fn compare_to_previous<F>(list: &Vec<isize>, predicate: F)  ->  bool
+fn compare_to_previous<F>(list: &[isize], predicate: F)  ->  bool
 where F: Fn(&isize, &isize) -> bool {
 
     let mut report_iter = list.iter();
-    let mut previous = report_iter.next().unwrap();
+    let mut previous = match report_iter.next() {
+        Some(previous) => previous,
+        // an empty report has nothing to compare
+        None => return true,
+    };
     for value in report_iter {
         if !predicate(value, previous) {
             return false
@@ -67,52 +243,6 @@ where F: Fn(&isize, &isize) -> bool {
     true
 }
 
-pub fn part_two() -> usize {
-    // get the lists from the txt file
-    let contents = fs::read_to_string("inputs/day_two_input.txt")
-        .expect("Should have been able to read the file");
-
-    let safe_report_count = contents
-        // split into lines
-        .split("\n")
-        // split and parse numbers collecting a Vec<isize>
-        .map(|x| {
-            x.split_whitespace()
-                .map(|y| y.parse::<isize>().unwrap())
-                .collect::<Vec<isize>>()
-        })
-        // expand each report to the set of possible reports
-        .map(|x| {
-            expand_report(&x)
-        })
-        // calculate if any of the reports in the set is safe. This means the original report was
-        // safe.
-        .map(|reports| {
-            reports
-                .iter()
-                .any(|report| {
-                    is_safe(report)
-                })
-        })
-        // filter out unsafe reports, and count the remaining safe ones
-        .filter(|x| *x )
-        .count();
-
-    println!("Safe report count: {safe_report_count}");
-    safe_report_count
-}
-
-fn expand_report(report: &Vec<isize>) -> Vec<Vec<isize>> {
-    let mut reports = Vec::new();
-    for i in 0..report.len() {
-        let mut new_report = report.clone();
-        new_report.remove(i);
-        reports.push(new_report);
-    }
-
-    reports
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,6 +268,70 @@ mod tests {
         assert!(!is_increasing(&report));
     }
 
+    #[test]
+    fn plateau_test() {
+        let report = vec![1, 2, 2, 4, 5];
+
+        assert!(!SafetyRules::part_one().is_safe(&report));
+        assert!(SafetyRules::part_two().is_safe(&report));
+
+        let rules = SafetyRules {
+            allow_plateaus: true,
+            ..SafetyRules::part_one()
+        };
+        assert!(rules.is_safe(&report));
+    }
+
+    #[test]
+    fn tolerance_test() {
+        let report = vec![1, 9, 2, 9, 3];
+
+        assert!(!SafetyRules::part_two().is_safe(&report));
+
+        let rules = SafetyRules {
+            tolerance: 2,
+            ..SafetyRules::part_one()
+        };
+        assert!(rules.is_safe(&report));
+    }
+
+    #[test]
+    fn parse_rules_test() {
+        let config = "
+            # any direction with big jumps
+            monotonicity = any
+            max_delta = 10
+            tolerance = 1
+        ";
+        let rules = SafetyRules::parse(config);
+
+        assert_eq!(rules, SafetyRules {
+            min_delta: 1,
+            max_delta: 10,
+            monotonicity: Monotonicity::Any,
+            allow_plateaus: false,
+            tolerance: 1,
+        });
+        assert!(rules.is_safe(&[1, 8, 3, 13]));
+        assert!(!rules.is_safe(&[1, 20, 3, 30]));
+    }
+
+    #[test]
+    fn parse_reports_test() {
+        let reports = parse_reports("7 6 4 2 1\r\n1 2 7 8 9\n\n");
+
+        assert_eq!(reports, vec![vec![7, 6, 4, 2, 1], vec![1, 2, 7, 8, 9]]);
+        assert_eq!(reports.iter().filter(|report| SafetyRules::part_one().is_safe(report)).count(), 1);
+    }
+
+    #[test]
+    fn rules_file_test() {
+        let rules = SafetyRules::from_file("inputs/day_two_rules.txt");
+
+        assert_eq!(rules, SafetyRules::part_two());
+        assert_eq!(count_safe_reports("inputs/day_two_input.txt", &rules), 349);
+    }
+
     #[test]
     fn part_one_test() {
         assert_eq!(part_one(), 282);
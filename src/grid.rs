@@ -0,0 +1,125 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// the rows of a rectangular grid from a puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridRows<'a> {
+    pub rows: Vec<&'a str>,
+    pub width: isize,
+    pub height: isize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    // the grids are indexed by byte, so every cell has to be a single ASCII character
+    NonAscii {
+        row: usize,
+        column: usize,
+    },
+    InvalidCell {
+        row: usize,
+        column: usize,
+        cell: char,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "the grid has no rows"),
+            GridError::RaggedRow { row, width, expected } => write!(
+                f,
+                "row {row} is {width} characters wide but the first row is {expected} characters wide",
+            ),
+            GridError::NonAscii { row, column } => write!(
+                f,
+                "row {row} has a non-ASCII character at column {column}",
+            ),
+            GridError::InvalidCell { row, column, cell } => write!(
+                f,
+                "row {row} has an invalid cell {cell:?} at column {column}",
+            ),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+// split a grid into rows. Both \n and \r\n line endings work, trailing newlines are ignored, every
+// row has to be as wide as the first, and only ASCII characters are allowed so widths in characters
+// and in bytes agree.
+pub fn parse_rows(input: &str) -> Result<GridRows<'_>, GridError> {
+    let mut rows = input.lines().collect::<Vec<&str>>();
+
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+
+    let expected = match rows.first() {
+        Some(row) => row.chars().count(),
+        None => return Err(GridError::Empty),
+    };
+
+    for (row, line) in rows.iter().enumerate() {
+        if let Some(column) = line.chars().position(|c| !c.is_ascii()) {
+            return Err(GridError::NonAscii { row, column })
+        }
+
+        let width = line.chars().count();
+        if width != expected {
+            return Err(GridError::RaggedRow { row, width, expected })
+        }
+    }
+
+    Ok(GridRows {
+        width: expected as isize,
+        height: rows.len() as isize,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_endings_test() {
+        let expected = GridRows {
+            rows: vec!["ab", "cd"],
+            width: 2,
+            height: 2,
+        };
+
+        assert_eq!(parse_rows("ab\ncd"), Ok(expected.clone()));
+        assert_eq!(parse_rows("ab\ncd\n"), Ok(expected.clone()));
+        assert_eq!(parse_rows("ab\r\ncd\r\n"), Ok(expected.clone()));
+        assert_eq!(parse_rows("ab\r\ncd\n\n"), Ok(expected));
+    }
+
+    #[test]
+    fn ragged_rows_test() {
+        let error = parse_rows("abc\nabc\nab\nabc").unwrap_err();
+
+        assert_eq!(error, GridError::RaggedRow { row: 2, width: 2, expected: 3 });
+        assert_eq!(error.to_string(), "row 2 is 2 characters wide but the first row is 3 characters wide");
+
+        assert_eq!(parse_rows("\n\n"), Err(GridError::Empty));
+    }
+
+    #[test]
+    fn non_ascii_test() {
+        // "é" is two bytes, so this row would be 3 bytes wide next to a 2 byte row
+        let error = parse_rows("ab\nxé").unwrap_err();
+
+        assert_eq!(error, GridError::NonAscii { row: 1, column: 1 });
+        assert_eq!(error.to_string(), "row 1 has a non-ASCII character at column 1");
+
+        assert_eq!(parse_rows("é\nab"), Err(GridError::NonAscii { row: 0, column: 0 }));
+    }
+}
@@ -1,3 +1,5 @@
+use std::env;
+
 mod day_one;
 mod day_two;
 mod day_three;
@@ -9,7 +11,43 @@ mod day_eight;
 mod day_nine;
 mod day_ten;
 mod day_eleven;
+mod big_int;
+mod grid;
 
 fn main() {
-    println!("hello world");
-}
\ No newline at end of file
+    // usage: advent_of_code_2024 <day> [options]
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let Some((day, options)) = args.split_first() else {
+        println!("usage: advent_of_code_2024 <day> [options]");
+        return
+    };
+
+    match day.as_str() {
+        "1" => print_parts(day_one::part_one(), day_one::part_two()),
+        "2" => day_two::run(options),
+        "3" => day_three::run(options),
+        "4" => day_four::run(options),
+        "5" => day_five::run(options),
+        "6" => day_six::run(options),
+        "7" => day_seven::run(options),
+        "8" => print_parts(day_eight::part_one(), day_eight::part_two()),
+        "9" => print_parts(day_nine::part_one(), day_nine::part_two()),
+        "10" => print_parts(day_ten::part_one(), day_ten::part_two()),
+        "11" => print_parts(day_eleven::part_one(), day_eleven::part_two()),
+        _ => println!("There is no solution for day {day}"),
+    }
+}
+
+fn print_parts<T: std::fmt::Display>(part_one: T, part_two: T) {
+    println!("Part one: {part_one}");
+    println!("Part two: {part_two}");
+}
+
+// the value given after an option, for example `--rules file.txt`
+fn option_value<'a>(options: &'a [String], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .position(|option| option == name)
+        .and_then(|index| options.get(index + 1))
+        .map(String::as_str)
+}